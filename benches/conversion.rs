@@ -0,0 +1,53 @@
+use betacode2::{to_greek, to_greek_bytes_into, to_greek_into, Type};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const DEFAULT_SENTENCE: &str = "E)n a)rch=| h)=n o( lo/gov, kai\\ o( lo/gov h)=n pro\\v to\\n qeo/n, kai\\ qeo\\v h)=n o( lo/gov. ";
+const TLG_SENTENCE: &str = "*)en a)rxh=| h)=n o( lo/gos, kai\\ o( lo/gos h)=n pro\\s to\\n qeo/n, kai\\ qeo\\s h)=n o( lo/gos. ";
+
+// Repeat a sentence to make a text of about the given length.
+fn text(sentence: &str, len: usize) -> String {
+    sentence.repeat(len.div_ceil(sentence.len()))
+}
+
+fn conversion(c: &mut Criterion) {
+    for (name, version, sentence) in [
+        ("default", Type::Default, DEFAULT_SENTENCE),
+        ("tlg", Type::TLG, TLG_SENTENCE),
+    ] {
+        let mut group = c.benchmark_group(format!("to_greek/{}", name));
+        let inputs = [
+            ("word", "lo/gos".to_string()),
+            ("1KB", text(sentence, 1024)),
+            ("1MB", text(sentence, 1024 * 1024)),
+        ];
+        for (size, input) in inputs.iter() {
+            group.throughput(Throughput::Bytes(input.len() as u64));
+            group.bench_with_input(BenchmarkId::new("to_greek", size), input, |b, input| {
+                b.iter(|| to_greek(black_box(input), version).unwrap())
+            });
+            group.bench_with_input(
+                BenchmarkId::new("to_greek_into", size),
+                input,
+                |b, input| {
+                    let mut word = String::new();
+                    b.iter(|| to_greek_into(black_box(input), version, &mut word).unwrap())
+                },
+            );
+            group.bench_with_input(
+                BenchmarkId::new("to_greek_bytes_into", size),
+                input,
+                |b, input| {
+                    let mut word = String::new();
+                    b.iter(|| {
+                        to_greek_bytes_into(black_box(input.as_bytes()), version, &mut word)
+                            .unwrap()
+                    })
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, conversion);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+#![no_main]
+
+use betacode2::{to_greek, to_greek_bytes, Type};
+use libfuzzer_sys::fuzz_target;
+
+// Conversion must return either Greek or an error for any input, and never
+// panic. Greek is always returned in a String, so it is valid UTF-8.
+fuzz_target!(|data: &[u8]| {
+    for version in [Type::Default, Type::TLG] {
+        let bytes = to_greek_bytes(data, version);
+        if let Ok(input) = std::str::from_utf8(data) {
+            assert_eq!(to_greek(input, version), bytes);
+        }
+        if let Ok(greek) = bytes {
+            assert!(std::str::from_utf8(greek.as_bytes()).is_ok());
+        }
+    }
+});
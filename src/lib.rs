@@ -27,44 +27,290 @@
 //! converter assumes all letters are always lowercase unless an asterix appears
 //! before the letter.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![forbid(unsafe_code)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
 /// Choose which betacode format to convert.
-#[derive(Copy, Clone, PartialEq)]
+///
+/// The default format is [`Type::Default`], the Robinson-Pierpont style
+/// betacode.
+///
+/// [`Type::TLG`] and [`Type::Perseus`] write a capital letter with an
+/// asterix, and ignore the case of ascii letters. They write xi as `c`, chi
+/// as `x` and digamma as `v`, where default betacode writes chi as `c` and a
+/// final sigma as `v`. Perseus betacode is TLG betacode that also reads `j`
+/// as a sigma, as the Perseus and Diogenes texts do. It is written by the
+/// same rules as `s`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum Type {
+    #[default]
     Default = 0,
     TLG = 1,
+    Perseus = 2,
+}
+
+impl Type {
+    // Capital letters are written with an asterix, and the case of ascii
+    // letters is ignored.
+    #[inline]
+    fn asterix_capitals(self) -> bool {
+        matches!(self, Type::TLG | Type::Perseus)
+    }
+}
+
+/// Parse a betacode format name, ignoring case.
+///
+/// `"default"`, `"standard"` and `"beta"` select [`Type::Default`],
+/// `"tlg"` selects [`Type::TLG`], and `"perseus"` selects [`Type::Perseus`].
+///
+/// ```
+/// use betacode2::Type;
+///
+/// assert_eq!("tlg".parse::<Type>().unwrap(), Type::TLG);
+/// assert_eq!("Default".parse::<Type>().unwrap(), Type::Default);
+/// assert!("latin".parse::<Type>().is_err());
+/// ```
+impl core::str::FromStr for Type {
+    type Err = ParseTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("default")
+            || s.eq_ignore_ascii_case("standard")
+            || s.eq_ignore_ascii_case("beta")
+        {
+            return Ok(Type::Default);
+        }
+        if s.eq_ignore_ascii_case("tlg") {
+            return Ok(Type::TLG);
+        }
+        if s.eq_ignore_ascii_case("perseus") {
+            return Ok(Type::Perseus);
+        }
+        Err(ParseTypeError(s.to_string()))
+    }
+}
+
+/// Read a betacode format from its name, in the same way as
+/// [`str::parse`].
+///
+/// ```
+/// use betacode2::Type;
+///
+/// assert_eq!(Type::try_from("tlg").unwrap(), Type::TLG);
+/// assert!(Type::try_from("latin").is_err());
+/// ```
+impl TryFrom<&str> for Type {
+    type Error = ParseTypeError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Write a betacode format as the string `"default"`, `"tlg"` or `"perseus"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Type {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Type::Default => "default",
+            Type::TLG => "tlg",
+            Type::Perseus => "perseus",
+        })
+    }
+}
+
+/// Read a betacode format from a string, accepting the same names as
+/// [`Type::from_str`](core::str::FromStr::from_str).
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Type {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+        struct TypeVisitor;
+
+        impl serde::de::Visitor<'_> for TypeVisitor {
+            type Value = Type;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a betacode type, 'default', 'tlg' or 'perseus'")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Type, E> {
+                s.parse()
+                    .map_err(|_| E::unknown_variant(s, &["default", "tlg", "perseus"]))
+            }
+        }
+
+        deserializer.deserialize_str(TypeVisitor)
+    }
+}
+
+/// Parsing a [`Type`] fails when the name is not a known betacode format.
+/// Returns the name that was not recognised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTypeError(pub String);
+
+impl fmt::Display for ParseTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown betacode type '{}', expected 'default', 'tlg' or 'perseus'",
+            self.0
+        )
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ParseTypeError {}
+
 /// Conversion fails when an unexpected character is found.
-#[derive(Debug)]
+///
+/// The position of the character is a byte offset into the input string,
+/// not into the converted output. Use [`ConversionError::char_index`] to
+/// count the position in characters instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ConversionError {
-    /// Returns the invalid character, and its position in the string.
+    /// Returns the invalid character, and its byte offset in the input.
     UnexpectedCharacter(char, usize),
-    /// Returns the character that has an invalid accent, and its position
-    /// in the string.
+    /// Returns the character that has an invalid accent, and its byte
+    /// offset in the input. Accents that contradict each other, such as
+    /// `a/\` or `a()`, and accents written twice are also invalid.
+    /// An accent after a sigma, as in `qeo/s/`, is an accent on the sigma
+    /// and is invalid, even at the end of a word or of the input.
     UnexpectedAccent(char, usize),
+    /// Returns the whole token holding the invalid betacode, that is the
+    /// text between the whitespace either side of it, with its start and
+    /// end byte offsets in the input, and the error found in the token.
+    ///
+    /// Only returned when [`Converter::with_token_errors`] is chosen, so
+    /// that an editor can underline the whole of a bad word.
+    InvalidToken {
+        token: String,
+        start: usize,
+        end: usize,
+        reason: Box<ConversionError>,
+    },
+    /// The writer given to [`write_greek`] or [`write_betacode`] returned
+    /// an error, so the output is incomplete. It has no position in the
+    /// input, and its byte offset is zero.
+    Write,
+}
+
+impl ConversionError {
+    /// Return the byte offset of the invalid character in the input.
+    pub fn byte_index(&self) -> usize {
+        match *self {
+            ConversionError::UnexpectedCharacter(_, i) => i,
+            ConversionError::UnexpectedAccent(_, i) => i,
+            ConversionError::InvalidToken { ref reason, .. } => reason.byte_index(),
+            ConversionError::Write => 0,
+        }
+    }
+
+    // Move the error along by n bytes, for text converted a piece at a time.
+    fn offset(self, n: usize) -> ConversionError {
+        match self {
+            ConversionError::UnexpectedCharacter(c, i) => {
+                ConversionError::UnexpectedCharacter(c, i + n)
+            }
+            ConversionError::UnexpectedAccent(c, i) => ConversionError::UnexpectedAccent(c, i + n),
+            ConversionError::InvalidToken {
+                token,
+                start,
+                end,
+                reason,
+            } => ConversionError::InvalidToken {
+                token,
+                start: start + n,
+                end: end + n,
+                reason: Box::new(reason.offset(n)),
+            },
+            ConversionError::Write => ConversionError::Write,
+        }
+    }
+
+    /// Return the position of the invalid character in the input, counted
+    /// in characters rather than bytes. The input must be the string that
+    /// was converted.
+    ///
+    /// ```
+    /// let input = "ἀρχῇ!";
+    /// let e = betacode2::to_betacode(input, betacode2::Type::TLG).unwrap_err();
+    /// assert_eq!(e.byte_index(), 10);
+    /// assert_eq!(e.char_index(input), 4);
+    /// ```
+    pub fn char_index(&self, input: &str) -> usize {
+        let i = self.byte_index();
+        input.char_indices().take_while(|&(j, _)| j < i).count()
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::UnexpectedCharacter(c, i) => {
+                write!(f, "unexpected character '{}' at byte {}", c, i)
+            }
+            ConversionError::UnexpectedAccent(c, i) => {
+                write!(f, "invalid accent on '{}' at byte {}", c, i)
+            }
+            ConversionError::InvalidToken {
+                token,
+                start,
+                end,
+                reason,
+            } => {
+                write!(
+                    f,
+                    "invalid token '{}' at bytes {}..{}: {}",
+                    token, start, end, reason
+                )
+            }
+            ConversionError::Write => write!(f, "the writer returned an error"),
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ConversionError {}
+
 pub trait Betacode {
     fn to_greek(&self, t: Type) -> Result<String, ConversionError>;
 }
 
 impl Betacode for String {
     fn to_greek(&self, t: Type) -> Result<String, ConversionError> {
-        to_greek(&self, t)
+        to_greek(self, t)
     }
 }
 
 impl Betacode for &str {
     fn to_greek(&self, t: Type) -> Result<String, ConversionError> {
-        to_greek(&self, t)
+        to_greek(self, t)
     }
 }
 
 /// Convert a betacode ascii string into a Greek unicode string.
 ///
-/// Space or punctuation characters should not appear at the start or end of
-/// the string. Unrecognised punctuation, ascii or unicode character cause
-/// an error to be returned.
+/// Words are separated by whitespace. Each run of spaces, tabs or newlines
+/// between two words is written as a single space, and whitespace at the
+/// start or end of the string is dropped. Commas and periods are kept, a
+/// colon is written as the Greek raised dot (ano teleia) and a semicolon as
+/// the Greek question mark. Whitespace before punctuation is dropped. An
+/// apostrophe at the end of a word is written as the elision mark `᾽`. The
+/// apostrophe must follow the last letter of the word, unless
+/// [`Converter::with_spaced_elision`] is chosen.
+/// A hyphen at the end of a line, or as the last character, marks a word
+/// broken across two lines. The hyphen and line break are dropped, joining
+/// the word, and a sigma before the hyphen is not written as a final sigma.
+/// Unrecognised punctuation, ascii or unicode character cause an error to be
+/// returned.
 ///
 /// # Examples
 ///
@@ -85,497 +331,6190 @@ impl Betacode for &str {
 /// The default converter assumes lowercase ascii letters are lowercase Greek
 /// letters and uppercase ascii letters are uppercase Greek letters. The TLG
 /// converter assumes all letters are always lowercase unless an asterix appears
-/// before the letter.
+/// before the letter. An asterix must be followed by its letter, after any
+/// accents of the letter, so an asterix on its own, or before whitespace or
+/// punctuation, returns an `UnexpectedCharacter` error.
+///
+/// In default betacode `s` and `j` are written as a final sigma `ς` at the
+/// end of a word and a medial sigma `σ` elsewhere, while `v` is always
+/// written as a final sigma. A sigma number after `v` and the lunate and
+/// medial [`SigmaPolicy`] still apply to it.
+///
+/// A circumflex may be written either `=` or `^`, so `a=` and `a^` are both
+/// `ᾶ`. Writing both after one letter is an accent written twice.
+///
+/// Chi is written `c` in default betacode and `x` in TLG betacode, where `c`
+/// is xi. Default betacode has no letter `x`, so `x` returns an
+/// `UnexpectedCharacter` error.
+///
+/// The TLG converter also reads the archaic letters koppa `#1` (`ϟ`), stigma
+/// `#2` (`ϛ`), archaic koppa `#3` (`ϙ`) and sampi `#5` (`ϡ`).
+///
+/// Greek numerals are letters marked with a hash. A hash after a letter is
+/// written as the numeral sign (keraia) `ʹ`, and a hash before a letter as
+/// the lower numeral sign `͵`, so `#awka#` is written `͵αωκαʹ` (1821). The
+/// lower numeral sign marks the thousands, and the hundreds, tens and units
+/// must follow in that order, each written at most once. A numeral sign
+/// after a word that is not a numeral, or a lower numeral sign that is not
+/// before a numeral letter, returns an `UnexpectedCharacter` error.
+///
+/// A breve `%26` or a macron `%27` marks the length of the vowel `a`, `i` or
+/// `u`. A long or short vowel with other accents is written with those accents
+/// as combining diacritics, as there is no precomposed form.
+///
+/// An underscore `_` is a dash, written as the em dash `—`. See
+/// [`Converter::with_dash`] to write a different dash.
 pub fn to_greek(input: &str, version: Type) -> Result<String, ConversionError> {
-    let mut word: String = String::new();
+    Converter::new(version).to_greek(input)
+}
 
-    let text = input.as_bytes();
-    let mut i: usize = 0;
-    let mut size: usize = input.len();
+/// Convert betacode ascii bytes into a Greek unicode string.
+///
+/// Betacode is plain ascii, so bytes read from a file or socket can be
+/// converted without first checking they are valid UTF-8. Any byte above
+/// 127 returns an `UnexpectedCharacter` error.
+///
+/// ```
+/// let word = betacode2::to_greek_bytes(b"qeo/s", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "θεός");
+/// ```
+pub fn to_greek_bytes(input: &[u8], version: Type) -> Result<String, ConversionError> {
+    Converter::new(version).to_greek_bytes(input)
+}
+
+/// Convert a betacode ascii string into a Greek unicode string, without
+/// allocating when the conversion produces no Greek text.
+///
+/// An empty or whitespace only string returns a borrowed empty string, so
+/// loops that skip over blank lines do not allocate.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let blank = betacode2::to_greek_cow("  ", betacode2::Type::TLG).unwrap();
+/// assert!(matches!(blank, Cow::Borrowed("")));
+///
+/// let word = betacode2::to_greek_cow("qeo/s", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "θεός");
+/// ```
+pub fn to_greek_cow(input: &str, version: Type) -> Result<Cow<'_, str>, ConversionError> {
+    Converter::new(version).to_greek_cow(input)
+}
+
+/// Convert a betacode ascii string into Greek, writing the result into a
+/// caller supplied string.
+///
+/// The string is cleared before conversion starts, so one buffer can be
+/// reused across many conversions without reallocating. If an error is
+/// returned the string holds the text converted before the error.
+///
+/// ```
+/// let mut word = String::with_capacity(64);
+/// betacode2::to_greek_into("qeo/s", betacode2::Type::TLG, &mut word).unwrap();
+/// assert_eq!(word, "θεός");
+/// betacode2::to_greek_into("lo/gos", betacode2::Type::TLG, &mut word).unwrap();
+/// assert_eq!(word, "λόγος");
+/// ```
+pub fn to_greek_into(input: &str, version: Type, word: &mut String) -> Result<(), ConversionError> {
+    Converter::new(version).to_greek_into(input, word)
+}
+
+/// Convert betacode ascii bytes into Greek, writing the result into a
+/// caller supplied string.
+///
+/// This is [`to_greek_bytes`] and [`to_greek_into`] together, so records
+/// read as bytes can be converted into one reused buffer, without checking
+/// they are valid UTF-8 and without allocating once the buffer is large
+/// enough. The string is cleared before conversion starts. If an error is
+/// returned the string holds the text converted before the error.
+///
+/// ```
+/// let mut word = String::with_capacity(64);
+/// betacode2::to_greek_bytes_into(b"qeo/s", betacode2::Type::TLG, &mut word).unwrap();
+/// assert_eq!(word, "θεός");
+/// betacode2::to_greek_bytes_into(b"lo/gos", betacode2::Type::TLG, &mut word).unwrap();
+/// assert_eq!(word, "λόγος");
+/// ```
+pub fn to_greek_bytes_into(
+    input: &[u8],
+    version: Type,
+    word: &mut String,
+) -> Result<(), ConversionError> {
+    Converter::new(version).to_greek_bytes_into(input, word)
+}
+
+/// Convert a betacode ascii string into a list of Greek characters.
+///
+/// ```
+/// let chars = betacode2::to_greek_chars("qeo/s", betacode2::Type::TLG).unwrap();
+/// assert_eq!(chars, ['θ', 'ε', 'ό', 'ς']);
+/// ```
+pub fn to_greek_chars(input: &str, version: Type) -> Result<Vec<char>, ConversionError> {
+    Converter::new(version).to_greek_chars(input)
+}
+
+/// Convert the first word of a betacode ascii string into Greek, returning
+/// the Greek word and the number of bytes read.
+///
+/// The bytes read include any whitespace before and after the word, so
+/// calling this again on the rest of the input converts the next word. An
+/// error is returned with its position in the whole input.
+///
+/// ```
+/// let input = "kai\\ qeo\\s h)=n";
+/// let (word, read) = betacode2::to_greek_token(input, betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "καὶ");
+/// assert_eq!(&input[read..], "qeo\\s h)=n");
+/// ```
+pub fn to_greek_token(input: &str, version: Type) -> Result<(String, usize), ConversionError> {
+    Converter::new(version).to_greek_token(input)
+}
+
+/// Convert betacode into Greek one word at a time, as the iterator is read.
+///
+/// Each whitespace separated word is converted on its own, with any
+/// punctuation written next to it, so an error in one word does not stop
+/// the words after it from being converted. Punctuation or an apostrophe
+/// written apart from the word before it is converted with that word, as
+/// it may end its clause or be its elision mark. Errors are returned with
+/// their position in the whole input.
+///
+/// ```
+/// use betacode2::{words, ConversionError, Type};
+///
+/// let greek: Vec<_> = words("kai\\ qeo!s h)=n", Type::TLG).collect();
+/// assert_eq!(
+///     greek,
+///     [
+///         Ok("καὶ".to_string()),
+///         Err(ConversionError::UnexpectedCharacter('!', 8)),
+///         Ok("ἦν".to_string()),
+///     ]
+/// );
+/// ```
+pub fn words(
+    input: &str,
+    version: Type,
+) -> impl Iterator<Item = Result<String, ConversionError>> + '_ {
+    Converter::new(version).words(input)
+}
+
+/// Convert a piece of betacode into Greek, given the betacode character
+/// that follows it, such as the first character of the next piece.
+///
+/// A sigma at the end of the input is written as a medial sigma `σ` when
+/// the next character is a letter, as the word carries on, and as a final
+/// sigma `ς` when it is whitespace, punctuation or `None`, the end of the
+/// text.
+///
+/// ```
+/// use betacode2::{to_greek_with_next, Type};
+///
+/// assert_eq!(to_greek_with_next("lo/gos", Type::TLG, None).unwrap(), "λόγος");
+/// assert_eq!(to_greek_with_next("lo/gos", Type::TLG, Some('a')).unwrap(), "λόγοσ");
+/// ```
+pub fn to_greek_with_next(
+    input: &str,
+    version: Type,
+    next: Option<char>,
+) -> Result<String, ConversionError> {
+    Converter::new(version).to_greek_with_next(input, next)
+}
+
+/// Check that a string is valid betacode, without building the Greek
+/// output string.
+///
+/// Returns the same error that [`to_greek`] would return for the string,
+/// as both functions share the same scanner.
+///
+/// ```
+/// use betacode2::{validate, Type};
+///
+/// assert!(validate("kai\\ qeo/s", Type::TLG).is_ok());
+/// assert!(validate("qeo/s!", Type::TLG).is_err());
+/// ```
+pub fn validate(input: &str, version: Type) -> Result<(), ConversionError> {
+    Converter::new(version).validate(input)
+}
+
+/// Convert a betacode ascii string into a Greek unicode string, replacing
+/// invalid betacode with the U+FFFD replacement character.
+///
+/// Conversion never fails. Each unexpected character, and each letter with
+/// an invalid accent, is written as `�` and conversion carries on, in the
+/// same way as `String::from_utf8_lossy`.
+///
+/// ```
+/// let word = betacode2::to_greek_lossy("qeo/s ! lo/gos", betacode2::Type::TLG);
+/// assert_eq!(word, "θεός \u{fffd} λόγος");
+/// ```
+pub fn to_greek_lossy(input: &str, version: Type) -> String {
+    Converter::new(version).to_greek_lossy(input)
+}
+
+/// Convert a betacode ascii string into a Greek unicode string, returning
+/// every error found instead of stopping at the first.
+///
+/// Invalid betacode is written as the U+FFFD replacement character, as in
+/// [`to_greek_lossy`], and each error is returned with its position in the
+/// input. The error list is empty when the input is valid.
+///
+/// ```
+/// use betacode2::{to_greek_all_errors, ConversionError, Type};
+///
+/// let (word, errors) = to_greek_all_errors("qeo/s! lo/gos!", Type::TLG);
+/// assert_eq!(word, "θεός\u{fffd} λόγος\u{fffd}");
+/// assert_eq!(
+///     errors,
+///     vec![
+///         ConversionError::UnexpectedCharacter('!', 5),
+///         ConversionError::UnexpectedCharacter('!', 13),
+///     ]
+/// );
+/// ```
+pub fn to_greek_all_errors(input: &str, version: Type) -> (String, Vec<ConversionError>) {
+    Converter::new(version).to_greek_all_errors(input)
+}
+
+/// Convert each line of a betacode ascii string into Greek on its own,
+/// returning one result for each line.
+///
+/// Lines are split on `\n`. An error is returned with the 0-based index of
+/// the line it was found on, and the error position is counted from the
+/// start of that line. An error on one line does not stop the other lines
+/// being converted.
+///
+/// ```
+/// use betacode2::{convert_lines, ConversionError, Type};
+///
+/// let lines = convert_lines("qeo/s\nlo/gos!\nkai/", Type::TLG);
+/// assert_eq!(lines[0], Ok("θεός".to_string()));
+/// assert_eq!(lines[1], Err((1, ConversionError::UnexpectedCharacter('!', 6))));
+/// assert_eq!(lines[2], Ok("καί".to_string()));
+/// ```
+pub fn convert_lines(input: &str, version: Type) -> Vec<Result<String, (usize, ConversionError)>> {
+    Converter::new(version).convert_lines(input)
+}
+
+/// Convert each string in a list of betacode strings in parallel, on the
+/// rayon thread pool, returning one result for each string in the same
+/// order. See [`Converter::convert_all`].
+///
+/// ```
+/// let words = betacode2::par_convert_all(&["qeo/s", "lo/gos"], betacode2::Type::TLG);
+/// assert_eq!(words, vec![Ok("θεός".to_string()), Ok("λόγος".to_string())]);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_convert_all(inputs: &[&str], version: Type) -> Vec<Result<String, ConversionError>> {
+    Converter::new(version).par_convert_all(inputs)
+}
+
+/// Convert a betacode ascii string into a Greek unicode string in Unicode
+/// normalization form D.
+///
+/// Accented letters are written as the base letter followed by combining
+/// diacritics, instead of the precomposed Greek Extended characters written
+/// by [`to_greek`]. The output has the same code points as running the
+/// output of [`to_greek`] through an NFD normalizer.
+///
+/// ```
+/// let word = betacode2::to_greek_nfd("e)/", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "ε\u{313}\u{301}");
+/// ```
+pub fn to_greek_nfd(input: &str, version: Type) -> Result<String, ConversionError> {
+    Converter::new(version).with_nfd(true).to_greek(input)
+}
+
+/// Convert a betacode ascii string into Greek written all in small letters.
+///
+/// A capital sigma becomes a final sigma `ς` at the end of a word, and a
+/// medial sigma `σ` elsewhere. Accents and breathings are kept.
+///
+/// ```
+/// let word = betacode2::to_greek_lower("*Q*E*O/*S", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "θεός");
+/// ```
+pub fn to_greek_lower(input: &str, version: Type) -> Result<String, ConversionError> {
+    Converter::new(version).to_greek_lower(input)
+}
+
+/// Convert a betacode ascii string into Greek written all in capital
+/// letters.
+///
+/// Accents and breathings are kept, so the output can be compared with
+/// other capitalised Greek, but is not how Greek is usually written in
+/// capitals. A letter with an iota subscript is written with a capital
+/// iota after it, following the Unicode case mapping, so `a|` is `ΑΙ`.
+///
+/// ```
+/// let word = betacode2::to_greek_upper("qeo/s", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "ΘΕΌΣ");
+/// ```
+pub fn to_greek_upper(input: &str, version: Type) -> Result<String, ConversionError> {
+    Converter::new(version).to_greek_upper(input)
+}
+
+/// Convert a betacode ascii string into Greek letters with every accent,
+/// breathing, diaeresis, iota subscript and length mark removed, for
+/// searching and comparing words.
+///
+/// The form of each sigma is kept. A converter with the
+/// [`SigmaPolicy::Medial`] sigma policy writes every sigma as `σ`.
+///
+/// ```
+/// let word = betacode2::to_greek_stripped("w(=|", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "ω");
+/// let word = betacode2::to_greek_stripped("qeo/s", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "θεος");
+/// ```
+pub fn to_greek_stripped(input: &str, version: Type) -> Result<String, ConversionError> {
+    Converter::new(version).to_greek_stripped(input)
+}
+
+/// Convert a betacode ascii string into a Greek unicode string, counting
+/// the letters, accented vowels, words and final sigmas written, as the
+/// Greek is written.
+///
+/// ```
+/// let (greek, stats) = betacode2::to_greek_with_stats("o( lo/gos", betacode2::Type::TLG).unwrap();
+/// assert_eq!(greek, "ὁ λόγος");
+/// assert_eq!(stats.words, 2);
+/// assert_eq!(stats.letters, 6);
+/// assert_eq!(stats.accented_vowels, 2);
+/// assert_eq!(stats.final_sigmas, 1);
+/// ```
+pub fn to_greek_with_stats(input: &str, version: Type) -> Result<(String, Stats), ConversionError> {
+    Converter::new(version).to_greek_with_stats(input)
+}
+
+/// Convert a Greek unicode string into a betacode ascii string.
+///
+/// Accented letters may be precomposed, or written as a base letter followed
+/// by combining diacritics, in any Unicode normalization form. The combining
+/// diacritics read are:
+///
+/// - U+0300 grave and U+0340 grave tone mark `\`
+/// - U+0301 acute and U+0341 acute tone mark `/`
+/// - U+0342 perispomeni `=`, which is never written as `^`, although `^`
+///   is read as a circumflex by [`to_greek`]
+/// - U+0308 diaeresis `+`, and U+0344 dialytika tonos `+/`
+/// - U+0313 psili and U+0343 koronis `)`
+/// - U+0314 dasia `(`
+/// - U+0345 ypogegrammeni `|`
+/// - U+0304 macron `%27` and U+0306 breve `%26`
+///
+/// A grave accent is always written `\`, including the grave written on
+/// the last syllable of an oxytone word inside a sentence, and is never
+/// changed into an acute `/`.
+///
+/// In TLG betacode a capital letter is written with
+/// an asterix, followed by its breathing and accents, then the letter. A
+/// sigma is written so that converting the betacode back into Greek gives
+/// the same form of sigma. The elision mark `᾽`, an ascii apostrophe and a
+/// right single quote U+2019 are all written as an apostrophe `'`.
+///
+/// Characters that have no betacode form return an `UnexpectedCharacter`
+/// error, and a letter with diacritics that betacode cannot express returns
+/// an `UnexpectedAccent` error, along with their byte position in the input.
+///
+/// ```
+/// let word = betacode2::to_betacode("Θεός", betacode2::Type::Default).unwrap();
+/// assert_eq!(word, "Qeo/s");
+///
+/// let word = betacode2::to_betacode("Ἀβραάμ", betacode2::Type::TLG).unwrap();
+/// assert_eq!(word, "*)abraa/m");
+/// ```
+pub fn to_betacode(input: &str, version: Type) -> Result<String, ConversionError> {
+    let mut betacode: String = String::with_capacity(input.len());
+    convert_greek(input, version, &mut betacode)?;
+    Ok(betacode)
+}
+
+/// Convert a betacode ascii string into Greek, writing the result into a
+/// formatter or any other [`fmt::Write`], without building a string. See
+/// [`to_greek`].
+///
+/// Once the writer returns an error, nothing more is written to it and
+/// [`ConversionError::Write`] is returned, unless the betacode is invalid.
+///
+/// ```
+/// use core::fmt;
+///
+/// struct Word(&'static str);
+///
+/// impl fmt::Display for Word {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         betacode2::write_greek(self.0, betacode2::Type::TLG, f).map_err(|_| fmt::Error)
+///     }
+/// }
+///
+/// assert_eq!(Word("qeo/s").to_string(), "θεός");
+/// ```
+pub fn write_greek<W: fmt::Write>(
+    input: &str,
+    version: Type,
+    out: &mut W,
+) -> Result<(), ConversionError> {
+    Converter::new(version).write_greek(input, out)
+}
+
+/// Convert a Greek unicode string into betacode, writing the result into a
+/// formatter or any other [`fmt::Write`], without building a string. See
+/// [`to_betacode`].
+///
+/// Once the writer returns an error, nothing more is written to it and
+/// [`ConversionError::Write`] is returned, unless the betacode is invalid.
+///
+/// ```
+/// let mut betacode = String::from("betacode: ");
+/// betacode2::write_betacode("θεός", betacode2::Type::TLG, &mut betacode).unwrap();
+/// assert_eq!(betacode, "betacode: qeo/s");
+/// ```
+pub fn write_betacode<W: fmt::Write>(
+    input: &str,
+    version: Type,
+    out: &mut W,
+) -> Result<(), ConversionError> {
+    let mut out = Fmt {
+        out,
+        empty: true,
+        failed: false,
+    };
+    convert_greek(input, version, &mut out)?;
+    out.result()
+}
+
+/// Return the Greek letter written by a single betacode letter, or `None`
+/// if the byte is not a betacode letter.
+///
+/// The letter is returned before any sigma rule is applied, so `s` returns
+/// the medial sigma `σ`. TLG betacode writes a capital letter with an
+/// asterix, so on its own an upper case letter returns the small letter.
+///
+/// ```
+/// use betacode2::{greek_letter, Type};
+///
+/// assert_eq!(greek_letter(b'q', Type::TLG), Some('θ'));
+/// assert_eq!(greek_letter(b'Q', Type::Default), Some('Θ'));
+/// assert_eq!(greek_letter(b'/', Type::TLG), None);
+/// ```
+pub fn greek_letter(c: u8, version: Type) -> Option<char> {
+    let c = match version {
+        Type::TLG | Type::Perseus => c.to_ascii_lowercase(),
+        Type::Default => c,
+    };
+    match lookup_greek_letter(c, version) {
+        l if l == 0 as char => None,
+        l => Some(l),
+    }
+}
+
+/// Return true if the byte is a betacode letter, which is written as a
+/// Greek letter. See [`greek_letter`].
+///
+/// ```
+/// use betacode2::{is_betacode_letter, Type};
+///
+/// assert!(is_betacode_letter(b'q', Type::TLG));
+/// assert!(is_betacode_letter(b'c', Type::Default));
+/// assert!(!is_betacode_letter(b'x', Type::Default));
+/// ```
+pub fn is_betacode_letter(c: u8, version: Type) -> bool {
+    greek_letter(c, version).is_some()
+}
+
+/// Return true if the byte is a betacode symbol written after a letter,
+/// that is a breathing, an accent, a diaeresis, an iota subscript or one of
+/// the sigma numbers `1`, `2` and `3`.
+///
+/// ```
+/// use betacode2::is_betacode_symbol;
+///
+/// assert!(is_betacode_symbol(b'/'));
+/// assert!(is_betacode_symbol(b'('));
+/// assert!(!is_betacode_symbol(b'a'));
+/// ```
+pub fn is_betacode_symbol(c: u8) -> bool {
+    !is_valid_betacode_symbol(c).is_empty()
+}
+
+/// Displays betacode as Greek, converting it as it is written, so that
+/// betacode can be passed straight to `format!` or `println!`.
+///
+/// Invalid betacode is written as the U+FFFD replacement character, as in
+/// [`to_greek_lossy`].
+///
+/// ```
+/// use betacode2::{GreekDisplay, Type};
+///
+/// assert_eq!(format!("{}", GreekDisplay("qeo/s", Type::TLG)), "θεός");
+/// assert_eq!(format!("{}", GreekDisplay("qeo/s!", Type::TLG)), "θεός\u{fffd}");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GreekDisplay<'a>(pub &'a str, pub Type);
+
+impl fmt::Display for GreekDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = Fmt {
+            out: f,
+            empty: true,
+            failed: false,
+        };
+        // Recovering from every error means no error is returned.
+        let _ = convert(
+            self.0.as_bytes(),
+            &Converter::new(self.1),
+            &mut out,
+            true,
+            &mut |_| Ok(()),
+        );
+        if out.failed {
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+/// Converts betacode into Greek using a set of conversion options.
+///
+/// The free functions such as [`to_greek`] use a converter with the
+/// default options for the chosen betacode format. Build a converter to
+/// change the options, then reuse it for any number of conversions.
+///
+/// ```
+/// use betacode2::{Converter, Type};
+///
+/// let converter = Converter::new(Type::TLG).with_nfd(true);
+/// assert_eq!(converter.to_greek("e)/").unwrap(), "ε\u{313}\u{301}");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Converter {
+    version: Type,
+    nfd: bool,
+    sigma: SigmaPolicy,
+    adscript: bool,
+    skip_markup: bool,
+    final_acute: bool,
+    keep_hyphens: bool,
+    punctuation: PunctuationPolicy,
+    rough_h: bool,
+    leading_accents: bool,
+    dash: char,
+    raised_dot: char,
+    spaced_elision: bool,
+    token_errors: bool,
+    drop_breathings: bool,
+    coronis: bool,
+    basic_greek: bool,
+    under_dot: bool,
+}
+
+/// Choose how a sigma is written.
+///
+/// A capital sigma has no final form, so it is written as `Σ` unless the
+/// lunate policy is chosen. The explicit betacode sigma forms `s1`, `s2`
+/// and `s3` are always written as medial, final and lunate sigma, whichever
+/// policy is chosen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SigmaPolicy {
+    /// Write a final sigma `ς` at the end of a word, and a medial sigma
+    /// `σ` everywhere else. A default betacode `v` is a final sigma
+    /// wherever it is.
+    Auto,
+    /// Write every sigma as a lunate sigma `ϲ`, and every capital sigma
+    /// as a capital lunate sigma `Ϲ`.
+    Lunate,
+    /// Write every sigma as a medial sigma `σ`, even at the end of a word.
+    Medial,
+}
+
+/// Choose what happens to ascii punctuation that has no Greek form.
+///
+/// Commas, periods, colons and semicolons are always written as Greek
+/// punctuation, and `(`, `)`, `/`, `\\`, `=`, `+` and `|` are always read as
+/// accents, whichever policy is chosen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PunctuationPolicy {
+    /// Return an `UnexpectedCharacter` error for unrecognised punctuation.
+    Strict,
+    /// Copy unrecognised ascii punctuation, such as quotes, dashes and
+    /// brackets, into the Greek unchanged.
+    ///
+    /// The editorial brackets `[`, `]`, `<` and `>` may fall inside a word,
+    /// so `[s]w/mata` becomes `[σ]ώματα`, with a medial sigma.
+    Passthrough,
+}
+
+/// A set of betacode accents, diacritics and sigma forms, as read after
+/// a letter.
+///
+/// Each mark is one bit of a `u16`. The twelve marks use the bits `0x1` to
+/// `0x800`, leaving room for two more before the set must be widened.
+///
+/// ```
+/// use betacode2::Accents;
+///
+/// let accents = Accents::SMOOTH | Accents::ACUTE;
+/// assert!(accents.contains(Accents::ACUTE));
+/// assert!(!accents.contains(Accents::GRAVE));
+/// assert_eq!(u16::from(accents), 0x21);
+/// assert_eq!(Accents::from_bits(0x21), Some(accents));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Accents(u16);
+
+impl Accents {
+    /// Acute accent `/`
+    pub const ACUTE: Accents = Accents(0x1);
+    /// Grave accent `\`
+    pub const GRAVE: Accents = Accents(0x2);
+    /// Circumflex accent `=`
+    pub const CIRCUMFLEX: Accents = Accents(0x4);
+    /// Diaeresis `+`
+    pub const DIAERESIS: Accents = Accents(0x8);
+    /// Rough breathing `(`
+    pub const ROUGH: Accents = Accents(0x10);
+    /// Smooth breathing `)`
+    pub const SMOOTH: Accents = Accents(0x20);
+    /// Iota subscript `|`
+    pub const IOTA: Accents = Accents(0x40);
+    /// Medial sigma `s1`
+    pub const SIGMA1: Accents = Accents(0x80);
+    /// Final sigma `s2`
+    pub const SIGMA2: Accents = Accents(0x100);
+    /// Lunate sigma `s3`
+    pub const SIGMA3: Accents = Accents(0x200);
+    /// Macron `%27`
+    pub const MACRON: Accents = Accents(0x400);
+    /// Breve `%26`
+    pub const BREVE: Accents = Accents(0x800);
+
+    /// Return a set with no accents.
+    pub const fn empty() -> Accents {
+        Accents(0)
+    }
+
+    /// Return a set with every accent.
+    pub const fn all() -> Accents {
+        Accents(0xfff)
+    }
+
+    /// Return the bits of the set.
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Return the set with these bits, or `None` if a bit is not an accent.
+    pub const fn from_bits(bits: u16) -> Option<Accents> {
+        if bits & !Accents::all().0 != 0 {
+            return None;
+        }
+        Some(Accents(bits))
+    }
+
+    /// Return the set with these bits, ignoring bits that are not accents.
+    pub const fn from_bits_truncate(bits: u16) -> Accents {
+        Accents(bits & Accents::all().0)
+    }
+
+    /// Return true if the set has no accents.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Return true if the set has every accent in `other`.
+    pub const fn contains(self, other: Accents) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Add the accents in `other` to the set.
+    pub fn insert(&mut self, other: Accents) {
+        self.0 |= other.0;
+    }
+
+    /// Remove the accents in `other` from the set.
+    pub fn remove(&mut self, other: Accents) {
+        self.0 &= !other.0;
+    }
+}
+
+impl core::ops::BitOr for Accents {
+    type Output = Accents;
+
+    fn bitor(self, other: Accents) -> Accents {
+        Accents(self.0 | other.0)
+    }
+}
+
+impl core::ops::BitOrAssign for Accents {
+    fn bitor_assign(&mut self, other: Accents) {
+        self.0 |= other.0;
+    }
+}
+
+impl core::ops::BitAnd for Accents {
+    type Output = Accents;
+
+    fn bitand(self, other: Accents) -> Accents {
+        Accents(self.0 & other.0)
+    }
+}
+
+impl From<Accents> for u16 {
+    fn from(accents: Accents) -> u16 {
+        accents.0
+    }
+}
+
+/// Counts of the Greek written by a conversion. See [`to_greek_with_stats`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Greek letters written, including archaic letters.
+    pub letters: usize,
+    /// Vowels written with any diacritic, that is a breathing, an accent,
+    /// a diaeresis, an iota subscript or a length mark.
+    pub accented_vowels: usize,
+    /// Words written, each an unbroken run of Greek letters.
+    pub words: usize,
+    /// Final sigmas `ς` written.
+    pub final_sigmas: usize,
+}
+
+impl Converter {
+    /// Create a converter for a betacode format, with default options.
+    pub fn new(version: Type) -> Converter {
+        Converter {
+            version,
+            nfd: false,
+            sigma: SigmaPolicy::Auto,
+            adscript: false,
+            skip_markup: false,
+            final_acute: false,
+            keep_hyphens: false,
+            punctuation: PunctuationPolicy::Strict,
+            rough_h: false,
+            leading_accents: false,
+            dash: '—',
+            raised_dot: '\u{387}',
+            spaced_elision: false,
+            token_errors: false,
+            drop_breathings: false,
+            coronis: false,
+            basic_greek: false,
+            under_dot: false,
+        }
+    }
+
+    /// Choose how a sigma is written. The default is [`SigmaPolicy::Auto`].
+    pub fn with_sigma(mut self, sigma: SigmaPolicy) -> Converter {
+        self.sigma = sigma;
+        self
+    }
+
+    /// Write accented letters as a base letter followed by combining
+    /// diacritics (Unicode normalization form D), instead of precomposed
+    /// characters.
+    pub fn with_nfd(mut self, nfd: bool) -> Converter {
+        self.nfd = nfd;
+        self
+    }
+
+    /// Write an iota subscript `|` as a following iota letter, the iota
+    /// adscript, so that `a|` becomes `αι` and `*a|` becomes `ΑΙ`, instead of
+    /// `ᾳ` and `ᾼ`.
+    pub fn with_adscript(mut self, adscript: bool) -> Converter {
+        self.adscript = adscript;
+        self
+    }
+
+    /// Skip the TLG formatting and font switch codes `&`, `$`, `@` and `"`,
+    /// along with any number written after them, such as `$1`. By default
+    /// these codes are errors.
+    pub fn with_skip_markup(mut self, skip_markup: bool) -> Converter {
+        self.skip_markup = skip_markup;
+        self
+    }
+
+    /// Write a grave accent as an acute accent on the last word before a
+    /// period, raised dot or question mark, where a clause ends. A grave
+    /// accent before a comma, or before another word, is kept, and an acute
+    /// accent is never changed, so `ti/;` is always `τί;`.
+    pub fn with_final_acute(mut self, final_acute: bool) -> Converter {
+        self.final_acute = final_acute;
+        self
+    }
+
+    /// Keep the hyphen and line break of a word broken across two lines,
+    /// instead of joining the two halves of the word. See [`to_greek`].
+    pub fn with_keep_hyphens(mut self, keep_hyphens: bool) -> Converter {
+        self.keep_hyphens = keep_hyphens;
+        self
+    }
+
+    /// Choose what happens to unrecognised ascii punctuation. The default
+    /// is [`PunctuationPolicy::Strict`].
+    pub fn with_punctuation(mut self, punctuation: PunctuationPolicy) -> Converter {
+        self.punctuation = punctuation;
+        self
+    }
+
+    /// Read an `h` at the start of a word, before a vowel, as a rough
+    /// breathing on that vowel, instead of the letter eta.
+    ///
+    /// Some older betacode, such as early Perseus texts, writes a rough
+    /// breathing as an `h` before the word, so `ho` is `ὁ`, where standard
+    /// betacode reads `ho` as `ηο` and writes `ὁ` as `o(`. The two readings
+    /// cannot be told apart from the text, so by default `h` is always eta.
+    ///
+    /// The breathing is written on the second vowel of a diphthong, so `hoi`
+    /// is `οἱ`, unless a diaeresis separates the vowels. A capital `H`
+    /// before a vowel makes the vowel a capital in the default format.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_rough_h(true);
+    /// assert_eq!(converter.to_greek("ho lo/gos").unwrap(), "ὁ λόγος");
+    /// assert_eq!(Converter::new(Type::TLG).to_greek("ho").unwrap(), "ηο");
+    /// ```
+    pub fn with_rough_h(mut self, rough_h: bool) -> Converter {
+        self.rough_h = rough_h;
+        self
+    }
+
+    /// Read breathings and accents written before a small letter as the
+    /// accents of that letter, so that `)a` becomes `ἀ`, as TLG betacode
+    /// writes the accents of a capital letter. By default an accent before
+    /// a letter is an error.
+    pub fn with_leading_accents(mut self, leading_accents: bool) -> Converter {
+        self.leading_accents = leading_accents;
+        self
+    }
+
+    /// Choose the character written for the betacode dash `_`. The default
+    /// is the em dash `—` (U+2014). Some Greek editions use the horizontal
+    /// bar `―` (U+2015) instead.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_dash('\u{2015}');
+    /// assert_eq!(converter.to_greek("_ kai\\ _").unwrap(), "\u{2015} καὶ \u{2015}");
+    /// ```
+    pub fn with_dash(mut self, dash: char) -> Converter {
+        self.dash = dash;
+        self
+    }
+
+    /// Choose the character written for the betacode raised dot `:`. The
+    /// default is the Greek ano teleia `·` (U+0387). Some fonts and tools
+    /// expect the middle dot `·` (U+00B7) instead, which is also what the ano
+    /// teleia becomes in Unicode normalization form D.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_raised_dot('\u{b7}');
+    /// assert_eq!(converter.to_greek("kalo/n:").unwrap(), "καλόν\u{b7}");
+    /// ```
+    pub fn with_raised_dot(mut self, raised_dot: char) -> Converter {
+        self.raised_dot = raised_dot;
+        self
+    }
+
+    /// Read an apostrophe written apart from the word before it, as in
+    /// `a)ll ' e)gw/`, as the elision mark of that word. The elision mark is
+    /// written straight after the word, and a space after it. By default
+    /// the apostrophe must follow the last letter of the word, and an
+    /// apostrophe after whitespace is an error.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_spaced_elision(true);
+    /// assert_eq!(converter.to_greek("a)ll ' e)gw/").unwrap(), "ἀλλ᾽ ἐγώ");
+    /// assert!(Converter::new(Type::TLG).to_greek("a)ll ' e)gw/").is_err());
+    /// ```
+    pub fn with_spaced_elision(mut self, spaced_elision: bool) -> Converter {
+        self.spaced_elision = spaced_elision;
+        self
+    }
+
+    /// Return a [`ConversionError::InvalidToken`] error, which holds the
+    /// whole whitespace separated token that the invalid betacode was found
+    /// in, instead of the error for the single invalid character.
+    ///
+    /// ```
+    /// use betacode2::{ConversionError, Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_token_errors(true);
+    /// match converter.to_greek("kai\\ qeo!s").unwrap_err() {
+    ///     ConversionError::InvalidToken { token, start, end, .. } => {
+    ///         assert_eq!((token.as_str(), start, end), ("qeo!s", 5, 10));
+    ///     }
+    ///     e => panic!("{}", e),
+    /// }
+    /// ```
+    pub fn with_token_errors(mut self, token_errors: bool) -> Converter {
+        self.token_errors = token_errors;
+        self
+    }
+
+    /// Read the breathings `(` and `)` without writing them, for Attic
+    /// inscriptions written before breathings were marked. Other accents
+    /// are kept, and [`Converter::to_greek_upper`] writes the text in the
+    /// capitals of an inscription.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_drop_breathings(true);
+    /// assert_eq!(converter.to_greek("*(ellas").unwrap(), "Ελλας");
+    /// assert_eq!(converter.to_greek_upper("*(ellas").unwrap(), "ΕΛΛΑΣ");
+    /// assert_eq!(Converter::new(Type::TLG).to_greek("*(ellas").unwrap(), "Ἑλλας");
+    /// ```
+    pub fn with_drop_breathings(mut self, drop_breathings: bool) -> Converter {
+        self.drop_breathings = drop_breathings;
+        self
+    }
+
+    /// Write a smooth breathing `)` on a vowel that follows a consonant of
+    /// the same word as the coronis U+0343, which marks crasis, so that
+    /// `ka)gw/` (`καὶ ἐγώ`) becomes `κα\u{343}γώ`. The coronis looks the same
+    /// as a smooth breathing, and is the same character once normalized, so
+    /// it is not kept when [`Converter::with_nfd`] is chosen.
+    ///
+    /// Betacode has no mark of its own for the coronis, so crasis is guessed
+    /// from where the breathing is written. Any smooth breathing after a
+    /// consonant of the same word becomes a coronis, even when an edition
+    /// writes it there for some other reason. Crasis written with a rough
+    /// breathing, such as `a(nh/r`, or at the start of a word, is not found
+    /// and keeps its breathing.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_coronis(true);
+    /// assert_eq!(converter.to_greek("ka)gw/").unwrap(), "κα\u{343}γώ");
+    /// assert_eq!(converter.to_greek("ou)k").unwrap(), "οὐκ");
+    /// ```
+    pub fn with_coronis(mut self, coronis: bool) -> Converter {
+        self.coronis = coronis;
+        self
+    }
+
+    /// Write only letters from the Greek and Coptic block (U+0370 to
+    /// U+03FF), each followed by its combining diacritics, and never the
+    /// precomposed letters of the Greek Extended block (U+1F00 to U+1FFF),
+    /// for fonts that have no Greek Extended characters. Letters are
+    /// decomposed as by [`Converter::with_nfd`], and the elision mark is
+    /// written as the right single quote `’` (U+2019) instead of `᾽`.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_basic_greek(true);
+    /// assert_eq!(converter.to_greek("e)/").unwrap(), "ε\u{313}\u{301}");
+    /// assert_eq!(converter.to_greek("a)p'").unwrap(), "α\u{313}π\u{2019}");
+    /// ```
+    pub fn with_basic_greek(mut self, basic_greek: bool) -> Converter {
+        self.basic_greek = basic_greek;
+        self
+    }
+
+    /// Read a question mark `?` after a letter as the under-dot of
+    /// papyrological editions, which marks an uncertain letter, and write it
+    /// as the combining dot below U+0323. The dot is written straight after
+    /// the letter, before any other diacritics that are not part of the
+    /// letter, so `a/?` becomes `ά\u{323}`. By default `?` is an error.
+    ///
+    /// ```
+    /// use betacode2::{Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG).with_under_dot(true);
+    /// assert_eq!(converter.to_greek("lo/?g?os").unwrap(), "λό\u{323}γ\u{323}ος");
+    /// ```
+    pub fn with_under_dot(mut self, under_dot: bool) -> Converter {
+        self.under_dot = under_dot;
+        self
+    }
+
+    // Return the accent written by a betacode symbol, or zero if the
+    // character is not a symbol.
+    #[inline]
+    fn symbol(&self, c: u8) -> u16 {
+        if c == b'?' && self.under_dot {
+            return ASCII_UNDER_DOT;
+        }
+        is_valid_betacode_symbol(c).bits()
+    }
+
+    // Letters are written as a base letter and combining diacritics.
+    #[inline]
+    fn decomposed(&self) -> bool {
+        self.nfd || self.basic_greek
+    }
+
+    // The character written for an apostrophe that marks elision.
+    #[inline]
+    fn elision(&self) -> char {
+        if self.basic_greek {
+            '\u{2019}'
+        } else {
+            '᾽'
+        }
+    }
+
+    /// Convert a betacode ascii string into a Greek unicode string.
+    /// See [`to_greek`].
+    pub fn to_greek(&self, input: &str) -> Result<String, ConversionError> {
+        self.to_greek_bytes(input.as_bytes())
+    }
+
+    /// Convert betacode ascii bytes into a Greek unicode string.
+    /// See [`to_greek_bytes`].
+    pub fn to_greek_bytes(&self, input: &[u8]) -> Result<String, ConversionError> {
+        let mut word: String = String::new();
+        self.to_greek_bytes_into(input, &mut word)?;
+        Ok(word)
+    }
+
+    /// Convert betacode, without allocating when the conversion produces
+    /// no Greek text. See [`to_greek_cow`].
+    pub fn to_greek_cow<'a>(&self, input: &'a str) -> Result<Cow<'a, str>, ConversionError> {
+        // String::new() does not allocate until the first character is pushed.
+        let mut word: String = String::new();
+        self.to_greek_into(input, &mut word)?;
+        if word.is_empty() {
+            return Ok(Cow::Borrowed(""));
+        }
+        Ok(Cow::Owned(word))
+    }
+
+    /// Convert betacode, writing the result into a formatter or any other
+    /// [`fmt::Write`]. See [`write_greek`].
+    pub fn write_greek<W: fmt::Write>(
+        &self,
+        input: &str,
+        out: &mut W,
+    ) -> Result<(), ConversionError> {
+        if self.final_acute {
+            // Accents are changed after the clause is converted.
+            let mut word: String = String::with_capacity(input.len());
+            self.convert(input.as_bytes(), &mut word, true, &mut Err)?;
+            return out.write_str(&word).map_err(|_| ConversionError::Write);
+        }
+        let mut out = Fmt {
+            out,
+            empty: true,
+            failed: false,
+        };
+        if self.decomposed() {
+            convert(input.as_bytes(), self, &mut Nfd(&mut out), true, &mut Err)?;
+        } else {
+            convert(input.as_bytes(), self, &mut out, true, &mut Err)?;
+        }
+        out.result()
+    }
+
+    /// Convert betacode, writing the result into a caller supplied string.
+    /// See [`to_greek_into`].
+    pub fn to_greek_into(&self, input: &str, word: &mut String) -> Result<(), ConversionError> {
+        self.to_greek_bytes_into(input.as_bytes(), word)
+    }
+
+    /// Convert betacode ascii bytes into Greek, writing the result into a
+    /// caller supplied string. See [`to_greek_bytes_into`].
+    pub fn to_greek_bytes_into(
+        &self,
+        input: &[u8],
+        word: &mut String,
+    ) -> Result<(), ConversionError> {
+        word.clear();
+        word.reserve(input.len());
+        self.convert(input, word, true, &mut Err)
+    }
+
+    /// Convert betacode into a list of Greek characters.
+    /// See [`to_greek_chars`].
+    pub fn to_greek_chars(&self, input: &str) -> Result<Vec<char>, ConversionError> {
+        if self.final_acute {
+            // Accents are changed after the clause is converted.
+            return Ok(self.to_greek(input)?.chars().collect());
+        }
+        let mut chars: Vec<char> = Vec::with_capacity(input.len());
+        if self.decomposed() {
+            convert(input.as_bytes(), self, &mut Nfd(&mut chars), true, &mut Err)?;
+        } else {
+            convert(input.as_bytes(), self, &mut chars, true, &mut Err)?;
+        }
+        Ok(chars)
+    }
+
+    /// Convert the first word of a betacode string, returning the Greek word
+    /// and the number of bytes read. See [`to_greek_token`].
+    pub fn to_greek_token(&self, input: &str) -> Result<(String, usize), ConversionError> {
+        let text = input.as_bytes();
+        let start = text
+            .iter()
+            .position(|&c| !is_ascii_whitespace(c))
+            .unwrap_or(text.len());
+        let end = text[start..]
+            .iter()
+            .position(|&c| is_ascii_whitespace(c))
+            .map_or(text.len(), |n| start + n);
+        let next = text[end..]
+            .iter()
+            .position(|&c| !is_ascii_whitespace(c))
+            .map_or(text.len(), |n| end + n);
+        // Leading whitespace is dropped, and error positions are counted
+        // from the start of the input.
+        let word = self.to_greek_bytes(&text[..end])?;
+        Ok((word, next))
+    }
+
+    /// Convert betacode into Greek one word at a time, as the iterator is
+    /// read. See [`words`].
+    pub fn words<'a>(
+        &self,
+        input: &'a str,
+    ) -> impl Iterator<Item = Result<String, ConversionError>> + 'a {
+        let options = *self;
+        let text = input.as_bytes();
+        let mut i = 0;
+        core::iter::from_fn(move || {
+            while i < text.len() && is_ascii_whitespace(text[i]) {
+                i += 1;
+            }
+            if i == text.len() {
+                return None;
+            }
+            let start = i;
+            let mut end = i;
+            // Anything after the whitespace that belongs to the word is
+            // converted along with it.
+            while i < text.len() && (i == start || !starts_word(text[i])) {
+                while i < text.len() && !is_ascii_whitespace(text[i]) {
+                    i += 1;
+                }
+                end = i;
+                while i < text.len() && is_ascii_whitespace(text[i]) {
+                    i += 1;
+                }
+            }
+            i = end;
+            Some(
+                options
+                    .to_greek_bytes(&text[start..end])
+                    .map_err(|e| e.offset(start)),
+            )
+        })
+    }
+
+    /// Convert betacode into Greek letters without accents or other
+    /// diacritics. See [`to_greek_stripped`].
+    pub fn to_greek_stripped(&self, input: &str) -> Result<String, ConversionError> {
+        // Decomposed letters are the base letter followed by its diacritics.
+        let greek = self.with_nfd(true).to_greek(input)?;
+        Ok(greek.chars().filter(|&c| !is_combining(c)).collect())
+    }
+
+    /// Convert betacode into Greek, counting the Greek written.
+    /// See [`to_greek_with_stats`].
+    pub fn to_greek_with_stats(&self, input: &str) -> Result<(String, Stats), ConversionError> {
+        let mut word: String = String::with_capacity(input.len());
+        let mut counted = Counted {
+            out: &mut word,
+            stats: Stats::default(),
+            letter: false,
+            vowel: false,
+            accented: false,
+        };
+        if self.decomposed() {
+            convert(
+                input.as_bytes(),
+                self,
+                &mut Nfd(&mut counted),
+                true,
+                &mut Err,
+            )?;
+        } else {
+            convert(input.as_bytes(), self, &mut counted, true, &mut Err)?;
+        }
+        let stats = counted.stats;
+        if self.final_acute {
+            final_acute(&mut word, 0);
+        }
+        Ok((word, stats))
+    }
+
+    /// Convert betacode into Greek written all in small letters. Capital
+    /// sigma is lowercased following the sigma policy. See [`to_greek_lower`].
+    pub fn to_greek_lower(&self, input: &str) -> Result<String, ConversionError> {
+        Ok(lowercase(&self.to_greek(input)?, self.sigma))
+    }
+
+    /// Convert betacode into Greek written all in capital letters.
+    /// See [`to_greek_upper`].
+    pub fn to_greek_upper(&self, input: &str) -> Result<String, ConversionError> {
+        Ok(self.to_greek(input)?.to_uppercase())
+    }
+
+    /// Convert a piece of betacode, given the betacode character that
+    /// follows it. See [`to_greek_with_next`].
+    pub fn to_greek_with_next(
+        &self,
+        input: &str,
+        next: Option<char>,
+    ) -> Result<String, ConversionError> {
+        let word_end = !next.is_some_and(|c| c.is_ascii_alphabetic());
+        let mut word: String = String::with_capacity(input.len());
+        self.convert(input.as_bytes(), &mut word, word_end, &mut Err)?;
+        Ok(word)
+    }
+
+    /// Check that a string is valid betacode. See [`validate`].
+    pub fn validate(&self, input: &str) -> Result<(), ConversionError> {
+        convert(
+            input.as_bytes(),
+            self,
+            &mut Discard { empty: true },
+            true,
+            &mut Err,
+        )
+    }
+
+    /// Convert betacode, replacing invalid betacode with the U+FFFD
+    /// replacement character. See [`to_greek_lossy`].
+    pub fn to_greek_lossy(&self, input: &str) -> String {
+        let mut word: String = String::with_capacity(input.len());
+        // Recovering from every error means no error is returned.
+        let _ = self.convert(input.as_bytes(), &mut word, true, &mut |_| Ok(()));
+        word
+    }
+
+    /// Convert betacode, returning every error found instead of stopping
+    /// at the first. See [`to_greek_all_errors`].
+    pub fn to_greek_all_errors(&self, input: &str) -> (String, Vec<ConversionError>) {
+        let mut word: String = String::with_capacity(input.len());
+        let mut errors: Vec<ConversionError> = Vec::new();
+        let _ = self.convert(input.as_bytes(), &mut word, true, &mut |e| {
+            errors.push(e);
+            Ok(())
+        });
+        (word, errors)
+    }
+
+    /// Convert each string in a list of betacode strings, returning one
+    /// result for each string.
+    ///
+    /// The strings are converted into one reused buffer, so each Greek
+    /// string returned is allocated once at its exact size. An error in one
+    /// string does not stop the other strings being converted.
+    ///
+    /// ```
+    /// use betacode2::{ConversionError, Converter, Type};
+    ///
+    /// let converter = Converter::new(Type::TLG);
+    /// let words = converter.convert_all(&["qeo/s", "lo/gos!"]);
+    /// assert_eq!(words[0], Ok("θεός".to_string()));
+    /// assert_eq!(words[1], Err(ConversionError::UnexpectedCharacter('!', 6)));
+    /// ```
+    pub fn convert_all(&self, inputs: &[&str]) -> Vec<Result<String, ConversionError>> {
+        let mut word = String::new();
+        inputs
+            .iter()
+            .map(|input| {
+                self.to_greek_into(input, &mut word)?;
+                Ok(word.as_str().into())
+            })
+            .collect()
+    }
+
+    /// Convert each string in a list of betacode strings in parallel.
+    /// See [`par_convert_all`].
+    #[cfg(feature = "rayon")]
+    pub fn par_convert_all(&self, inputs: &[&str]) -> Vec<Result<String, ConversionError>> {
+        use rayon::prelude::*;
+        inputs
+            .par_iter()
+            .map(|input| self.to_greek(input))
+            .collect()
+    }
+
+    /// Convert each line of betacode on its own. See [`convert_lines`].
+    pub fn convert_lines(&self, input: &str) -> Vec<Result<String, (usize, ConversionError)>> {
+        input
+            .split('\n')
+            .enumerate()
+            .map(|(line, text)| self.to_greek(text).map_err(|e| (line, e)))
+            .collect()
+    }
+
+    // Convert into a string, decomposing the output when NFD is selected.
+    fn convert<R>(
+        &self,
+        input: &[u8],
+        word: &mut String,
+        word_end: bool,
+        recover: &mut R,
+    ) -> Result<(), ConversionError>
+    where
+        R: FnMut(ConversionError) -> Result<(), ConversionError>,
+    {
+        let start = word.len();
+        if self.decomposed() {
+            convert(input, self, &mut Nfd(word), word_end, recover)?;
+        } else {
+            convert(input, self, word, word_end, recover)?;
+        }
+        if self.final_acute {
+            final_acute(word, start);
+        }
+        Ok(())
+    }
+}
+
+/// Write betacode, converting it into UTF-8 Greek written to another writer.
+///
+/// Betacode is held until the next word is started, so a letter and its
+/// accents may be split across calls to `write`. Calling
+/// `flush` ends the word being written, and writes any letter still waiting,
+/// such as a final sigma. Invalid betacode returns an error of kind
+/// [`std::io::ErrorKind::InvalidData`] wrapping a [`ConversionError`], with
+/// its position counted from the first byte written. When `write` returns
+/// an error, from the betacode or from the wrapped writer, none of the buffer
+/// is taken, so it may be written again.
+///
+/// ```
+/// use std::io::Write;
+/// use betacode2::{Converter, GreekWriter, Type};
+///
+/// let mut writer = GreekWriter::new(Vec::new(), Converter::new(Type::TLG));
+/// writer.write_all(b"e)n a)rxh=| h)=n o( lo/g").unwrap();
+/// writer.write_all(b"os").unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.get_ref(), "ἐν ἀρχῇ ἦν ὁ λόγος".as_bytes());
+/// ```
+#[cfg(feature = "std")]
+pub struct GreekWriter<W: std::io::Write> {
+    inner: W,
+    words: Words,
+    // Betacode after the last whitespace, waiting for the end of its word
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> GreekWriter<W> {
+    /// Create a writer converting betacode with a set of conversion options.
+    pub fn new(inner: W, options: Converter) -> GreekWriter<W> {
+        GreekWriter {
+            inner,
+            words: Words::new(options),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Return a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Flush any waiting betacode, and return the wrapped writer.
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        std::io::Write::flush(&mut self)?;
+        Ok(self.inner)
+    }
+
+    // Convert the waiting betacode up to the end of a word, and remove it
+    // once the Greek is written. On an error nothing is removed, and the
+    // words are converted again by the next call.
+    fn convert(&mut self, end: usize) -> std::io::Result<()> {
+        let mut greek = String::with_capacity(end + 1);
+        let mut words = self.words;
+        words
+            .convert(&self.pending[..end], &mut greek)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.inner.write_all(greek.as_bytes())?;
+        self.words = words;
+        self.pending.drain(..end);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> std::io::Write for GreekWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.pending.len();
+        self.pending.extend_from_slice(buf);
+        // Convert up to the start of the last word, keeping each run of
+        // whitespace with the word before it.
+        let start = self
+            .pending
+            .windows(2)
+            .rposition(|w| is_ascii_whitespace(w[0]) && starts_word(w[1]));
+        if let Some(start) = start {
+            if let Err(e) = self.convert(start + 1) {
+                // No bytes of the buffer were written.
+                self.pending.truncate(len);
+                return Err(e);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.convert(self.pending.len())?;
+        self.inner.flush()
+    }
+}
+
+/// An iterator over the Greek characters converted from an iterator of
+/// betacode bytes.
+///
+/// Betacode is read one word at a time, only as the Greek characters are
+/// needed. Invalid betacode returns a [`ConversionError`], with its position
+/// counted from the first byte read, and then the iterator ends.
+///
+/// ```
+/// use betacode2::{Converter, GreekChars, Type};
+///
+/// let input = "e)n a)rxh=| h)=n o( lo/gos";
+/// let chars = GreekChars::new(input.bytes(), Converter::new(Type::TLG));
+/// let first: String = chars.map(Result::unwrap).take_while(|&c| c != ' ').collect();
+/// assert_eq!(first, "ἐν");
+/// ```
+pub struct GreekChars<I: Iterator<Item = u8>> {
+    input: core::iter::Peekable<I>,
+    words: Words,
+    // Greek converted from the last word read
+    greek: String,
+    // Position of the next character to return from the Greek
+    next: usize,
+    // The end of the input was reached, or an error was returned
+    done: bool,
+}
+
+impl<I: Iterator<Item = u8>> GreekChars<I> {
+    /// Create an iterator converting betacode with a set of conversion options.
+    pub fn new(input: I, options: Converter) -> GreekChars<I> {
+        GreekChars {
+            input: input.peekable(),
+            words: Words::new(options),
+            greek: String::new(),
+            next: 0,
+            done: false,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for GreekChars<I> {
+    type Item = Result<char, ConversionError>;
+
+    fn next(&mut self) -> Option<Result<char, ConversionError>> {
+        loop {
+            if let Some(c) = self.greek[self.next..].chars().next() {
+                self.next += c.len_utf8();
+                return Some(Ok(c));
+            }
+            if self.done {
+                return None;
+            }
+            // Read the next word, and the whitespace after it, along with
+            // anything after the whitespace that belongs to the word.
+            let mut word: Vec<u8> = Vec::new();
+            loop {
+                while let Some(c) = self.input.next_if(|&c| !is_ascii_whitespace(c)) {
+                    word.push(c);
+                }
+                while let Some(c) = self.input.next_if(|&c| is_ascii_whitespace(c)) {
+                    word.push(c);
+                }
+                match self.input.peek() {
+                    Some(&c) if !starts_word(c) => {}
+                    _ => break,
+                }
+            }
+            if self.input.peek().is_none() {
+                self.done = true;
+            }
+            self.greek.clear();
+            self.next = 0;
+            if let Err(e) = self.words.convert(&word, &mut self.greek) {
+                self.done = true;
+                self.greek.clear();
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+// Converts betacode a few words at a time, as if it was all converted at once.
+#[derive(Copy, Clone)]
+struct Words {
+    options: Converter,
+    // Number of betacode bytes converted so far
+    position: usize,
+    // Whitespace was read since the last Greek was written
+    space: bool,
+    // Greek has been written
+    written: bool,
+}
+
+impl Words {
+    fn new(options: Converter) -> Words {
+        Words {
+            options,
+            position: 0,
+            space: false,
+            written: false,
+        }
+    }
+
+    // Convert betacode that ends at the end of a word, adding the Greek
+    // onto the string.
+    fn convert(&mut self, words: &[u8], greek: &mut String) -> Result<(), ConversionError> {
+        let mut converted = String::with_capacity(words.len());
+        let position = self.position;
+        self.options
+            .convert(words, &mut converted, true, &mut Err)
+            .map_err(|e| e.offset(position))?;
+        self.position += words.len();
+        if words.first().is_some_and(|&c| is_ascii_whitespace(c)) {
+            self.space = true;
+        }
+        if !converted.is_empty() {
+            // Words are separated by a single space, as when the betacode
+            // is converted all at once.
+            let first = words.iter().find(|&&c| !is_ascii_whitespace(c));
+            if self.space
+                && self.written
+                && first.is_some_and(|&c| lookup_punctuation(c) == 0 as char)
+            {
+                greek.push(' ');
+            }
+            greek.push_str(&converted);
+            self.written = true;
+            self.space = false;
+        }
+        // A word broken by a hyphen at the end of a line carries on
+        // in the next betacode converted.
+        let end = words.iter().rposition(|&c| !is_ascii_whitespace(c));
+        let hyphen = end.is_some_and(|end| words[end] == b'-');
+        if words.last().is_some_and(|&c| is_ascii_whitespace(c)) && !hyphen {
+            self.space = true;
+        }
+        Ok(())
+    }
+}
+
+// Receives the Greek characters written while scanning betacode.
+trait Output {
+    fn push(&mut self, c: char);
+    fn is_empty(&self) -> bool;
+}
+
+impl Output for String {
+    #[inline]
+    fn push(&mut self, c: char) {
+        String::push(self, c)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        String::is_empty(self)
+    }
+}
+
+impl Output for Vec<char> {
+    #[inline]
+    fn push(&mut self, c: char) {
+        Vec::push(self, c)
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        Vec::is_empty(self)
+    }
+}
+
+// Output that throws away the characters written to it, used when
+// validating betacode.
+struct Discard {
+    empty: bool,
+}
+
+impl Output for Discard {
+    #[inline]
+    fn push(&mut self, _: char) {
+        self.empty = false
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.empty
+    }
+}
+
+// Output that writes into a formatter. A writer error stops any
+// more characters being written.
+struct Fmt<'a, W: fmt::Write> {
+    out: &'a mut W,
+    empty: bool,
+    failed: bool,
+}
+
+impl<W: fmt::Write> Fmt<'_, W> {
+    // Report a writer error once the conversion has finished.
+    fn result(&self) -> Result<(), ConversionError> {
+        if self.failed {
+            return Err(ConversionError::Write);
+        }
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> Output for Fmt<'_, W> {
+    #[inline]
+    fn push(&mut self, c: char) {
+        if !self.failed {
+            self.failed = self.out.write_char(c).is_err();
+        }
+        self.empty = false
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.empty
+    }
+}
+
+// Output that decomposes each character written to it (Unicode NFD).
+struct Nfd<'a, O: Output>(&'a mut O);
+
+impl<O: Output> Output for Nfd<'_, O> {
+    #[inline]
+    fn push(&mut self, c: char) {
+        match decompose(c) {
+            Some(d) => {
+                for e in d.chars() {
+                    self.0.push(e);
+                }
+            }
+            None => self.0.push(c),
+        }
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Output that counts the Greek written through it.
+struct Counted<'a, O: Output> {
+    out: &'a mut O,
+    stats: Stats,
+    // The last character written was a letter or one of its diacritics
+    letter: bool,
+    // The last letter written is a vowel
+    vowel: bool,
+    // The last letter written was counted as an accented vowel
+    accented: bool,
+}
+
+impl<O: Output> Output for Counted<'_, O> {
+    #[inline]
+    fn push(&mut self, c: char) {
+        if is_combining(c) {
+            if self.letter && self.vowel && !self.accented {
+                self.stats.accented_vowels += 1;
+                self.accented = true;
+            }
+        } else if c.is_alphabetic() && c != '\u{374}' {
+            // The numeral keraia is a modifier letter, but not a letter of
+            // the word.
+            let d = decompose(c);
+            let base = d.and_then(|d| d.chars().next()).unwrap_or(c);
+            if !self.letter {
+                self.stats.words += 1;
+            }
+            self.stats.letters += 1;
+            self.letter = true;
+            self.vowel = is_vowel(base);
+            self.accented = self.vowel && d.is_some();
+            if self.accented {
+                self.stats.accented_vowels += 1;
+            }
+            if c == 'ς' {
+                self.stats.final_sigmas += 1;
+            }
+        } else {
+            self.letter = false;
+        }
+        self.out.push(c);
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.out.is_empty()
+    }
+}
+
+// Output that writes the smooth breathing of a vowel after a consonant of
+// the same word as a coronis, which marks crasis.
+struct Coronis<'a, O: Output> {
+    out: &'a mut O,
+    // A consonant has been written in the current word
+    consonant: bool,
+}
+
+impl<O: Output> Output for Coronis<'_, O> {
+    #[inline]
+    fn push(&mut self, c: char) {
+        if c.is_alphabetic() {
+            let d = decompose(c).unwrap_or("");
+            let base = d.chars().next().unwrap_or(c);
+            let vowel = is_vowel(base);
+            if vowel && self.consonant && d.contains('\u{313}') {
+                for e in d.chars() {
+                    self.out.push(if e == '\u{313}' { '\u{343}' } else { e });
+                }
+                return;
+            }
+            self.consonant |= !vowel;
+        } else if !is_combining(c) {
+            self.consonant = false;
+        }
+        self.out.push(c);
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.out.is_empty()
+    }
+}
+
+// Scan betacode and write the converted Greek characters to the output.
+//
+// Invalid betacode is passed to `recover`. If `recover` returns the error
+// the conversion stops, otherwise a replacement character is written in
+// place of the invalid input and the conversion continues. The text ends
+// at the end of a word, unless `word_end` is false, when the word carries
+// on after the text.
+fn convert<O, R>(
+    text: &[u8],
+    options: &Converter,
+    word: &mut O,
+    word_end: bool,
+    recover: &mut R,
+) -> Result<(), ConversionError>
+where
+    O: Output,
+    R: FnMut(ConversionError) -> Result<(), ConversionError>,
+{
+    if options.coronis {
+        let mut word = Coronis {
+            out: word,
+            consonant: false,
+        };
+        return scan(text, options, &mut word, word_end, recover);
+    }
+    scan(text, options, word, word_end, recover)
+}
+
+fn scan<O, R>(
+    text: &[u8],
+    options: &Converter,
+    word: &mut O,
+    word_end: bool,
+    report: &mut R,
+) -> Result<(), ConversionError>
+where
+    O: Output,
+    R: FnMut(ConversionError) -> Result<(), ConversionError>,
+{
+    let recover = &mut |e: ConversionError| {
+        if options.token_errors {
+            report(token_error(text, e))
+        } else {
+            report(e)
+        }
+    };
+    let version = options.version;
+    let mut i: usize = 0;
+    let size: usize = text.len();
+
+    // Read a character and any accents following it
+    let mut current = Letter {
+        c: 0 as char,
+        accents: 0,
+        index: 0,
+        final_sigma: false,
+    };
+    let mut uppercase: bool = false;
+    // Index of the asterix that marks the next letter as a capital
+    let mut asterix: usize = 0;
+    let mut prefix: u16 = 0;
+    // Whitespace was read since the last letter was pushed
+    let mut space: bool = false;
+    // The whitespace being read follows the last letter of a word
+    let mut after_word: bool = false;
+    // An elision mark was pushed, so the word must end here
+    let mut elided: bool = false;
+    // A letter was read since the start of the word
+    let mut letters: bool = false;
+    // Index of the vowel that takes the rough breathing written as an
+    // `h`, and of the vowel made a capital by an `H`.
+    let mut rough: Option<usize> = None;
+    let mut capital: Option<usize> = None;
+    // The letters of the word read so far can be read as a numeral, and the
+    // next letter must be worth less than this place value. Zero means the
+    // word is not a numeral.
+    let mut numeral: u32 = u32::MAX;
+    // A lower numeral sign was read, so the next letter counts thousands.
+    let mut thousands: bool = false;
+
+    loop {
+        if i == size {
+            break;
+        }
+        let mut c = text[i];
+        if uppercase
+            && !(c.is_ascii_alphabetic()
+                || c == b'%'
+                || c == b'#'
+                || options.symbol(c) != 0
+                || (options.skip_markup && is_markup(c)))
+        {
+            // An asterix must be followed by the letter it marks as a
+            // capital, after any accents of the letter.
+            push_letter(word, options, &mut current, true, recover)?;
+            uppercase = false;
+            prefix = 0;
+            recover(ConversionError::UnexpectedCharacter('*', asterix))?;
+            word.push(REPLACEMENT);
+        }
+        if is_ascii_whitespace(c) {
+            // Whitespace ends a word, so a letter waiting to be
+            // pushed is the last letter of the word.
+            after_word = current.c != 0 as char || (space && after_word);
+            push_letter(word, options, &mut current, true, recover)?;
+            space = true;
+            elided = false;
+            letters = false;
+            numeral = u32::MAX;
+            i += 1;
+            continue;
+        }
+        if options.skip_markup && is_markup(c) {
+            // Formatting codes are not part of the text, and do not
+            // end the word they appear in.
+            i += 1;
+            while i < size && text[i].is_ascii_digit() {
+                i += 1;
+            }
+            continue;
+        }
+        if c == b'*' && version.asterix_capitals() {
+            // The asterix writes nothing itself, so any whitespace
+            // before it is written before the capital letter.
+            uppercase = true;
+            asterix = i;
+            i += 1;
+            continue;
+        }
+        // Accents of a capital letter are written with the letter, so
+        // whitespace before them is written before the letter.
+        let prefix_symbol = uppercase && (c == b'%' || options.symbol(c) != 0);
+        if c == b'\'' && space && after_word && !uppercase && options.spaced_elision {
+            // The apostrophe belongs to the word before the whitespace,
+            // and the next word is written after a space.
+            word.push(options.elision());
+            after_word = false;
+            i += 1;
+            continue;
+        }
+        if space && !prefix_symbol {
+            // Each run of whitespace between two words is written
+            // as a single space. Leading whitespace is dropped, as is
+            // whitespace before punctuation.
+            if !word.is_empty() && lookup_punctuation(c) == 0 as char {
+                word.push(' ');
+            }
+            space = false;
+        }
+        if elided && lookup_punctuation(c) == 0 as char {
+            // An elided word must end at the apostrophe.
+            elided = false;
+            recover(ConversionError::UnexpectedCharacter(c as char, i))?;
+            word.push(REPLACEMENT);
+            i = skip_unicode(text, i);
+            continue;
+        }
+        if c == b'-' && current.c != 0 as char {
+            // A hyphen at the end of a line joins the two halves of a word,
+            // so the letter before it is not the last letter of the word.
+            let mut j = i + 1;
+            while j < size && is_ascii_whitespace(text[j]) && text[j] != b'\n' {
+                j += 1;
+            }
+            if i + 1 == size || (j < size && text[j] == b'\n') {
+                push_letter(word, options, &mut current, false, recover)?;
+                if options.keep_hyphens {
+                    word.push('-');
+                    if j < size {
+                        word.push('\n');
+                    }
+                }
+                i = j;
+                while i < size && is_ascii_whitespace(text[i]) {
+                    i += 1;
+                }
+                continue;
+            }
+        }
+        if c == b'*' {
+            push_letter(word, options, &mut current, false, recover)?;
+            recover(ConversionError::UnexpectedCharacter(c as char, i))?;
+            word.push(REPLACEMENT);
+            i += 1;
+            continue;
+        }
+        if c > 127 {
+            // Unicode sequences should not appear
+            // in ascii betacode sequences
+            push_letter(word, options, &mut current, false, recover)?;
+            recover(ConversionError::UnexpectedCharacter(c as char, i))?;
+            word.push(REPLACEMENT);
+            i = skip_unicode(text, i);
+            continue;
+        }
+        if c == b'%' {
+            // A percent sign and a number marks the length of a vowel.
+            let start = i;
+            let mut n: u32 = 0;
+            i += 1;
+            while i < size && text[i].is_ascii_digit() {
+                n = n.saturating_mul(10).saturating_add((text[i] - b'0') as u32);
+                i += 1;
+            }
+            let mark = lookup_length_mark(n);
+            if mark != 0 && uppercase {
+                prefix = add_accent(prefix, mark);
+                continue;
+            }
+            if mark != 0 && current.c != 0 as char {
+                current.accents = add_accent(current.accents, mark);
+                numeral = 0;
+                continue;
+            }
+            push_letter(word, options, &mut current, true, recover)?;
+            recover(ConversionError::UnexpectedCharacter(c as char, start))?;
+            word.push(REPLACEMENT);
+            continue;
+        }
+        if c == b'#' {
+            let start = i;
+            i += 1;
+            if i == size || !text[i].is_ascii_digit() {
+                // A hash with no number is the numeral sign (keraia) after
+                // a letter, or the lower numeral sign before a letter.
+                if current.c != 0 as char {
+                    push_letter(word, options, &mut current, false, recover)?;
+                    if numeral == 0 {
+                        recover(ConversionError::UnexpectedCharacter(c as char, start))?;
+                        word.push(REPLACEMENT);
+                    } else {
+                        word.push('\u{374}');
+                    }
+                    numeral = 0;
+                } else if numeral_after(text, i, version) {
+                    word.push('\u{375}');
+                    thousands = true;
+                } else {
+                    recover(ConversionError::UnexpectedCharacter(c as char, start))?;
+                    word.push(REPLACEMENT);
+                }
+                continue;
+            }
+            // TLG writes archaic letters as a hash and a number.
+            let mut n: u32 = 0;
+            while i < size && text[i].is_ascii_digit() {
+                n = n.saturating_mul(10).saturating_add((text[i] - b'0') as u32);
+                i += 1;
+            }
+            let l = match version {
+                Type::TLG | Type::Perseus => lookup_numbered_letter(n, uppercase),
+                Type::Default => 0 as char,
+            };
+            if l != 0 as char {
+                push_letter(word, options, &mut current, false, recover)?;
+                current = Letter {
+                    c: l,
+                    accents: prefix,
+                    index: start,
+                    final_sigma: false,
+                };
+                numeral = next_numeral(numeral, thousands, &current);
+                thousands = false;
+                prefix = 0;
+                uppercase = false;
+                letters = true;
+                continue;
+            }
+            push_letter(word, options, &mut current, true, recover)?;
+            recover(ConversionError::UnexpectedCharacter(c as char, start))?;
+            word.push(REPLACEMENT);
+            continue;
+        }
+        if version.asterix_capitals() {
+            if uppercase {
+                if c.is_ascii_lowercase() {
+                    c -= b'a' - b'A'
+                }
+            } else if c.is_ascii_uppercase() {
+                c += b'a' - b'A'
+            }
+        }
+        if options.rough_h
+            && !letters
+            && !uppercase
+            && (c == b'h' || (c == b'H' && version == Type::Default))
+        {
+            let vowel = rough_breathing_vowel(text, i + 1);
+            if vowel != 0 {
+                // The h is a rough breathing, not a letter.
+                rough = Some(vowel);
+                if c == b'H' {
+                    capital = Some(i + 1);
+                }
+                i += 1;
+                continue;
+            }
+        }
+        let l = lookup_greek_letter(c, version);
+        if l != 0 as char {
+            // We encountered the next letter, if we just read a previous
+            // letter, push it onto the return string.
+            push_letter(word, options, &mut current, false, recover)?;
+            // The start of a letter sequence, which takes any accents
+            // written between the asterix and the letter.
+            current = Letter {
+                c: l,
+                accents: prefix,
+                index: i,
+                final_sigma: version == Type::Default && c == b'v',
+            };
+            if capital == Some(i) {
+                current.c = l.to_uppercase().next().unwrap_or(l);
+                capital = None;
+            }
+            if rough == Some(i) {
+                current.accents = add_accent(current.accents, ASCII_ROUGH);
+                rough = None;
+            }
+            numeral = next_numeral(numeral, thousands, &current);
+            thousands = false;
+            prefix = 0;
+            uppercase = false;
+            letters = true;
+            i += 1;
+            continue;
+        }
+        let valid = options.symbol(c);
+        if valid > 0 {
+            if uppercase {
+                // TLG writes the accents of an uppercase letter
+                // between the asterix and the letter.
+                prefix = add_accent(prefix, valid);
+                i += 1;
+                continue;
+            }
+            if current.c == 0 as char
+                && options.leading_accents
+                && accents_before_letter(text, i, version)
+            {
+                // The accents are written before the letter they belong to.
+                prefix = add_accent(prefix, valid);
+                i += 1;
+                continue;
+            }
+            if current.c == 0 as char {
+                // We see a betacode accent character, but
+                // not a greek letter just before it.
+                recover(ConversionError::UnexpectedCharacter(c as char, i))?;
+                word.push(REPLACEMENT);
+                i += 1;
+                continue;
+            }
+            current.accents = add_accent(current.accents, valid);
+            // A letter with an accent is not a numeral.
+            numeral = 0;
+            i += 1;
+            continue;
+        }
+
+        if options.punctuation == PunctuationPolicy::Passthrough && is_editorial_bracket(c) {
+            // Editorial brackets mark letters restored or added inside a
+            // word, so a word carries on after them.
+            let mut j = i + 1;
+            while j < size && is_editorial_bracket(text[j]) {
+                j += 1;
+            }
+            let word_end = j == size || !(text[j].is_ascii_alphabetic() || text[j] == b'*');
+            push_letter(word, options, &mut current, word_end, recover)?;
+            word.push(c as char);
+            i += 1;
+            continue;
+        }
+
+        if c == b'\'' && current.c != 0 as char {
+            // An apostrophe after a letter marks an elided word.
+            push_letter(word, options, &mut current, true, recover)?;
+            word.push(options.elision());
+            elided = true;
+            i += 1;
+            continue;
+        }
+
+        // Anything else ends a word, so a letter waiting to be
+        // pushed is the last letter of the word.
+        push_letter(word, options, &mut current, true, recover)?;
+        letters = false;
+        numeral = u32::MAX;
+        let p = lookup_punctuation(c);
+        if p != 0 as char {
+            if p == '\u{387}' {
+                word.push(options.raised_dot);
+            } else {
+                word.push(p);
+            }
+            elided = false;
+            i += 1;
+            continue;
+        }
+        if c == b'_' {
+            // A dash is written with the spaces around it.
+            word.push(options.dash);
+            elided = false;
+            i += 1;
+            continue;
+        }
+        if options.punctuation == PunctuationPolicy::Passthrough && c.is_ascii_punctuation() {
+            word.push(c as char);
+            i += 1;
+            continue;
+        }
+        // This character is not an alphabetic letter, not a whitespace,
+        // not a valid betacode symbol and not punctuation.
+        recover(ConversionError::UnexpectedCharacter(c as char, i))?;
+        word.push(REPLACEMENT);
+        i += 1;
+    }
+
+    // When the end of string is reached, a final character
+    // may be waiting to be pushed onto the result string.
+    push_letter(word, options, &mut current, word_end, recover)?;
+    if uppercase {
+        recover(ConversionError::UnexpectedCharacter('*', asterix))?;
+        word.push(REPLACEMENT);
+    }
+    Ok(())
+}
+
+// Wrap an error in the whitespace separated token it was found in.
+fn token_error(text: &[u8], e: ConversionError) -> ConversionError {
+    let i = e.byte_index().min(text.len());
+    let mut start = i;
+    while start > 0 && !is_ascii_whitespace(text[start - 1]) {
+        start -= 1;
+    }
+    let mut end = i;
+    while end < text.len() && !is_ascii_whitespace(text[end]) {
+        end += 1;
+    }
+    ConversionError::InvalidToken {
+        token: String::from_utf8_lossy(&text[start..end]).into_owned(),
+        start,
+        end,
+        reason: Box::new(e),
+    }
+}
+
+// Return the index of the next character after the byte at index i,
+// skipping over the rest of a multibyte UTF-8 sequence.
+#[inline]
+fn skip_unicode(text: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < text.len() && text[i] & 0xc0 == 0x80 {
+        i += 1;
+    }
+    i
+}
+
+// Return the value of a letter written as a Greek numeral, or zero if the
+// letter is not a numeral.
+fn numeral_value(c: char) -> u32 {
+    let c = c.to_lowercase().next().unwrap_or(c);
+    match c {
+        'α' => 1,
+        'β' => 2,
+        'γ' => 3,
+        'δ' => 4,
+        'ε' => 5,
+        'ϛ' | 'ϝ' => 6,
+        'ζ' => 7,
+        'η' => 8,
+        'θ' => 9,
+        'ι' => 10,
+        'κ' => 20,
+        'λ' => 30,
+        'μ' => 40,
+        'ν' => 50,
+        'ξ' => 60,
+        'ο' => 70,
+        'π' => 80,
+        'ϟ' | 'ϙ' => 90,
+        'ρ' => 100,
+        'σ' => 200,
+        'τ' => 300,
+        'υ' => 400,
+        'φ' => 500,
+        'χ' => 600,
+        'ψ' => 700,
+        'ω' => 800,
+        'ϡ' => 900,
+        _ => 0,
+    }
+}
+
+// Return the place value the letter after this one must be worth less
+// than, for a word read as a numeral. The units, tens and hundreds of a
+// numeral are written in that order, each at most once, and a letter after
+// a lower numeral sign counts thousands. Zero means the word is not a
+// numeral.
+fn next_numeral(numeral: u32, thousands: bool, letter: &Letter) -> u32 {
+    let value = numeral_value(letter.c);
+    if value == 0 || letter.accents != 0 {
+        return 0;
+    }
+    if thousands {
+        return 1000;
+    }
+    let place = match value {
+        1..=9 => 1,
+        10..=90 => 10,
+        _ => 100,
+    };
+    if place >= numeral {
+        return 0;
+    }
+    place
+}
+
+// Return true if a lower numeral sign at index i is followed by a letter
+// that can be written as a numeral.
+fn numeral_after(text: &[u8], mut i: usize, version: Type) -> bool {
+    if version.asterix_capitals() && text.get(i) == Some(&b'*') {
+        i += 1;
+    }
+    let Some(&c) = text.get(i) else {
+        return false;
+    };
+    if c == b'#' && version.asterix_capitals() {
+        let digits = text[i + 1..].iter().take_while(|d| d.is_ascii_digit());
+        let n = digits.fold(0u32, |n, &d| {
+            n.saturating_mul(10).saturating_add((d - b'0') as u32)
+        });
+        return numeral_value(lookup_numbered_letter(n, false)) != 0;
+    }
+    let c = match version {
+        Type::TLG | Type::Perseus => c.to_ascii_lowercase(),
+        Type::Default => c,
+    };
+    numeral_value(lookup_greek_letter(c, version)) != 0
+}
+
+// Square brackets mark letters restored by an editor, and angle brackets
+// letters added by an editor.
+#[inline]
+fn is_editorial_bracket(c: u8) -> bool {
+    matches!(c, b'[' | b']' | b'<' | b'>')
+}
+
+// Return true if the accents starting at index i are followed by a letter.
+fn accents_before_letter(text: &[u8], mut i: usize, version: Type) -> bool {
+    while i < text.len() && !is_valid_betacode_symbol(text[i]).is_empty() {
+        i += 1;
+    }
+    let c = match (text.get(i), version) {
+        (Some(c), Type::TLG | Type::Perseus) => c.to_ascii_lowercase(),
+        (Some(&c), Type::Default) => c,
+        (None, _) => return false,
+    };
+    lookup_greek_letter(c, version) != 0 as char
+}
+
+// Return the index of the vowel that takes a rough breathing written as an
+// `h` before the vowel at index i, or zero if there is no vowel there. The
+// breathing of a diphthong is written on its second vowel.
+fn rough_breathing_vowel(text: &[u8], i: usize) -> usize {
+    let vowel = |i: usize| text.get(i).map_or(0, |c| c.to_ascii_lowercase());
+    let first = vowel(i);
+    if !matches!(first, b'a' | b'e' | b'h' | b'i' | b'o' | b'u' | b'w') {
+        return 0;
+    }
+    let second = vowel(i + 1);
+    let diphthong = (second == b'i' && matches!(first, b'a' | b'e' | b'o' | b'u'))
+        || (second == b'u' && matches!(first, b'a' | b'e' | b'h' | b'o'));
+    if diphthong && text.get(i + 2) != Some(&b'+') {
+        return i + 1;
+    }
+    i
+}
+
+// A letter read from the input, with the accents read after it so far.
+// The letter waits to be pushed until the next letter or the end of the
+// word is reached. A zero letter means no letter is waiting.
+struct Letter {
+    c: char,
+    accents: u16,
+    index: usize,
+    // A sigma always written as a final sigma by the automatic sigma
+    // policy, wherever it is in the word
+    final_sigma: bool,
+}
+
+// Push the waiting letter with its accents onto the result string, if
+// there is one. An unaccented sigma is written following the sigma policy,
+// which by default writes a final sigma when the letter is the last letter
+// of a word.
+#[inline]
+fn push_letter<O, R>(
+    word: &mut O,
+    options: &Converter,
+    letter: &mut Letter,
+    word_end: bool,
+    recover: &mut R,
+) -> Result<(), ConversionError>
+where
+    O: Output,
+    R: FnMut(ConversionError) -> Result<(), ConversionError>,
+{
+    if options.drop_breathings {
+        letter.accents &= !(ASCII_ROUGH | ASCII_SMOOTH);
+    }
+    let c = letter.c;
+    let accents = letter.accents;
+    if c == 0 as char {
+        return Ok(());
+    }
+    if is_contradictory(accents) {
+        letter.c = 0 as char;
+        recover(ConversionError::UnexpectedAccent(c, letter.index))?;
+        word.push(REPLACEMENT);
+        return Ok(());
+    }
+    if accents & ASCII_UNDER_DOT != 0 {
+        // The dot below comes before the other diacritics in normal form,
+        // so it is written after the letter and any diacritic that is part
+        // of a precomposed letter, or after the base letter when decomposed.
+        letter.accents &= !ASCII_UNDER_DOT;
+        let mut greek = String::new();
+        push_letter(&mut greek, options, letter, word_end, recover)?;
+        let mut chars = greek.chars();
+        if let Some(first) = chars.next() {
+            let d = decompose(first).filter(|_| options.decomposed());
+            let mut base = d.unwrap_or("").chars();
+            word.push(base.next().unwrap_or(first));
+            if first != REPLACEMENT {
+                word.push('\u{323}');
+            }
+            for e in base.chain(chars) {
+                word.push(e);
+            }
+        }
+        return Ok(());
+    }
+    if options.adscript
+        && accents & ASCII_IOTA != 0
+        && matches!(c, 'α' | 'η' | 'ω' | 'Α' | 'Η' | 'Ω')
+    {
+        // Write the letter with its other accents, then the iota after it.
+        letter.accents &= !ASCII_IOTA;
+        push_letter(word, options, letter, word_end, recover)?;
+        word.push(if c.is_uppercase() { 'Ι' } else { 'ι' });
+        return Ok(());
+    }
+    letter.c = 0 as char;
+    if accents & ASCII_LENGTH != 0 {
+        // There are no precomposed letters with both a length mark and
+        // another accent, so other accents follow as combining diacritics.
+        let long = apply_length(c, accents & ASCII_LENGTH);
+        let rest = accents & !ASCII_LENGTH;
+        let marks = if rest == 0 {
+            Some("")
+        } else {
+            let e = apply_accent(c, rest);
+            if e != 0 as char {
+                decompose(e)
+            } else {
+                apply_combining_accent(c, rest)
+            }
+            .and_then(|d| d.strip_prefix(c))
+        };
+        match marks {
+            Some(marks) if long != 0 as char => {
+                word.push(long);
+                for e in marks.chars() {
+                    word.push(e);
+                }
+            }
+            _ => {
+                recover(ConversionError::UnexpectedAccent(c, letter.index))?;
+                word.push(REPLACEMENT);
+            }
+        }
+        return Ok(());
+    }
+    // Only a sigma without accents takes the sigma policy. A sigma with an
+    // accent is never written as a medial or final sigma, it falls through
+    // to the accent table, which has no accented sigma, and is an error.
+    if accents == 0 && c == 'σ' {
+        match options.sigma {
+            SigmaPolicy::Auto if word_end || letter.final_sigma => word.push('ς'),
+            SigmaPolicy::Auto | SigmaPolicy::Medial => word.push('σ'),
+            SigmaPolicy::Lunate => word.push('ϲ'),
+        }
+        return Ok(());
+    }
+    if accents == 0 && c == 'Σ' && options.sigma == SigmaPolicy::Lunate {
+        word.push('Ϲ');
+        return Ok(());
+    }
+    let e = apply_accent(c, accents);
+    if e != 0 as char {
+        word.push(e);
+        return Ok(());
+    }
+    match apply_combining_accent(c, accents) {
+        Some(d) => {
+            for e in d.chars() {
+                word.push(e);
+            }
+            Ok(())
+        }
+        None => {
+            recover(ConversionError::UnexpectedAccent(c, letter.index))?;
+            word.push(REPLACEMENT);
+            Ok(())
+        }
+    }
+}
+
+// Write Greek in small letters. A capital sigma at the end of a word is
+// written as a final sigma when the sigma policy is automatic.
+fn lowercase(greek: &str, sigma: SigmaPolicy) -> String {
+    let mut out = String::with_capacity(greek.len());
+    let mut chars = greek.chars().peekable();
+    // The last character was part of a word
+    let mut letter = false;
+    while let Some(c) = chars.next() {
+        if c == 'Σ' && sigma == SigmaPolicy::Auto {
+            let word_end = !chars
+                .peek()
+                .is_some_and(|&n| n.is_alphabetic() || is_combining(n));
+            out.push(if letter && word_end { 'ς' } else { 'σ' });
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        letter = c.is_alphabetic() || (letter && is_combining(c));
+    }
+    out
+}
+
+// Return true for a combining diacritic written after a letter.
+#[inline]
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36f}')
+}
+
+// Change a grave accent on the last word of each clause into an acute
+// accent, in the Greek written after the start offset.
+fn final_acute(word: &mut String, start: usize) {
+    let mut out = String::with_capacity(word.len());
+    out.push_str(&word[..start]);
+    // Position of the last grave accent in the current word
+    let mut grave: Option<usize> = None;
+    for c in word[start..].chars() {
+        match c {
+            '.' | '\u{387}' | '\u{b7}' | '\u{37e}' => {
+                if let Some(i) = grave {
+                    let g = out[i..].chars().next().unwrap();
+                    out.replace_range(
+                        i..i + g.len_utf8(),
+                        grave_to_acute(g).encode_utf8(&mut [0; 4]),
+                    );
+                }
+                grave = None;
+            }
+            ' ' | ',' | '᾽' | '\u{2019}' => grave = None,
+            _ if grave_to_acute(c) != c => grave = Some(out.len()),
+            _ => {}
+        }
+        out.push(c);
+    }
+    *word = out;
+}
+
+// Return the letter with its grave accent changed to an acute accent,
+// or the same character if it has no grave accent.
+fn grave_to_acute(c: char) -> char {
+    if c == '\u{300}' {
+        return '\u{301}';
+    }
+    let Some(d) = decompose(c) else {
+        return c;
+    };
+    let mut chars = d.chars();
+    let base = chars.next().unwrap();
+    let accents = chars.fold(0, |a, m| a | lookup_combining_accent(m));
+    if accents & ASCII_GRAVE == 0 {
+        return c;
+    }
+    apply_accent(base, accents - ASCII_GRAVE + ASCII_ACUTE)
+}
+
+// Add an accent read after a letter. An accent written twice is marked
+// as repeated, so that it is reported as an error.
+#[inline]
+fn add_accent(accents: u16, accent: u16) -> u16 {
+    if accents & accent != 0 {
+        return accents | ASCII_REPEATED;
+    }
+    accents | accent
+}
+
+// Return true if a letter has accents that cannot be written together,
+// such as both an acute and a grave accent, or both breathings, or an
+// accent written twice.
+#[inline]
+fn is_contradictory(accents: u16) -> bool {
+    let accent = accents & (ASCII_ACUTE | ASCII_GRAVE | ASCII_CIRCUMFLEX);
+    let breathing = accents & (ASCII_ROUGH | ASCII_SMOOTH);
+    let sigma = accents & ASCII_SIGMA;
+    accents & ASCII_REPEATED != 0
+        || accent.count_ones() > 1
+        || breathing.count_ones() > 1
+        || sigma.count_ones() > 1
+        || accents & ASCII_LENGTH == ASCII_LENGTH
+}
+
+// TLG writes archaic letters as a hash followed by a number:
+//
+//   #1  ϟ Ϟ  koppa
+//   #2  ϛ Ϛ  stigma
+//   #3  ϙ Ϙ  archaic koppa
+//   #5  ϡ Ϡ  sampi
+//
+// Returns zero when the number is not an archaic letter.
+fn lookup_numbered_letter(n: u32, uppercase: bool) -> char {
+    match (n, uppercase) {
+        (1, false) => 'ϟ',
+        (1, true) => 'Ϟ',
+        (2, false) => 'ϛ',
+        (2, true) => 'Ϛ',
+        (3, false) => 'ϙ',
+        (3, true) => 'Ϙ',
+        (5, false) => 'ϡ',
+        (5, true) => 'Ϡ',
+        _ => 0 as char,
+    }
+}
+
+// Return the Greek punctuation mark written for a betacode punctuation
+// character, or zero if the character is not punctuation.
+#[inline]
+fn lookup_punctuation(c: u8) -> char {
+    match c {
+        b',' => ',',
+        b'.' => '.',
+        b':' => '\u{387}',
+        b';' => '\u{37e}',
+        _ => 0 as char,
+    }
+}
+
+// test if a character is a valid accentuation for a Greek character.
+//
+// See: https://stephanus.tlg.uci.edu/encoding/BCM.pdf
+#[inline]
+fn is_valid_betacode_symbol(c: u8) -> Accents {
+    match c {
+        b'/' => Accents::ACUTE,
+        b'\\' => Accents::GRAVE,
+        b'(' => Accents::ROUGH,
+        b')' => Accents::SMOOTH,
+        b'|' => Accents::IOTA,
+        b'+' => Accents::DIAERESIS,
+        b'=' => Accents::CIRCUMFLEX,
+        // Read as a circumflex, but always written back as `=`.
+        b'^' => Accents::CIRCUMFLEX,
+        b'1' => Accents::SIGMA1,
+        b'2' => Accents::SIGMA2,
+        b'3' => Accents::SIGMA3,
+        _ => Accents::empty(),
+    }
+}
+
+// Betacode marks vowel length with a percent sign and a number:
+//
+//   %26  ˘  breve, a short vowel
+//   %27  ¯  macron, a long vowel
+//
+// Returns zero when the number is not a length mark.
+fn lookup_length_mark(n: u32) -> u16 {
+    match n {
+        26 => ASCII_BREVE,
+        27 => ASCII_MACRON,
+        _ => 0,
+    }
+}
+
+// TLG formatting and font switch codes, which may be followed by a number.
+#[inline]
+fn is_markup(c: u8) -> bool {
+    matches!(c, b'&' | b'$' | b'@' | b'"')
+}
+
+// Written in place of invalid betacode when conversion carries on
+// after an error.
+const REPLACEMENT: char = '\u{fffd}';
+
+const ASCII_ACUTE: u16 = Accents::ACUTE.bits();
+const ASCII_GRAVE: u16 = Accents::GRAVE.bits();
+const ASCII_CIRCUMFLEX: u16 = Accents::CIRCUMFLEX.bits();
+const ASCII_DIAERESIS: u16 = Accents::DIAERESIS.bits();
+const ASCII_ROUGH: u16 = Accents::ROUGH.bits();
+const ASCII_SMOOTH: u16 = Accents::SMOOTH.bits();
+const ASCII_IOTA: u16 = Accents::IOTA.bits();
+const ASCII_SIGMA1: u16 = Accents::SIGMA1.bits();
+const ASCII_SIGMA2: u16 = Accents::SIGMA2.bits();
+const ASCII_SIGMA3: u16 = Accents::SIGMA3.bits();
+const ASCII_MACRON: u16 = Accents::MACRON.bits();
+const ASCII_BREVE: u16 = Accents::BREVE.bits();
+
+const ASCII_SIGMA: u16 = ASCII_SIGMA1 + ASCII_SIGMA2 + ASCII_SIGMA3;
+const ASCII_LENGTH: u16 = ASCII_MACRON + ASCII_BREVE;
+// An accent was written twice after the same letter
+const ASCII_REPEATED: u16 = 0x8000;
+// The letter is uncertain, and written with a dot below. Not a betacode
+// accent, so it is not one of the Accents.
+const ASCII_UNDER_DOT: u16 = 0x1000;
+
+// The accents of a letter are held in a u16. The twelve marks take the low
+// bits 0x1 to 0x800, the under-dot takes 0x1000, and the top bit flags a
+// repeated mark, which leaves bits 0x2000 and 0x4000 free for new marks.
+// A new mark must take one of those bits and be added to Accents::all(),
+// or the accents must be widened to a u32. These checks stop a new mark from sharing a bit with another.
+const _: () = assert!(
+    (ASCII_ACUTE
+        ^ ASCII_GRAVE
+        ^ ASCII_CIRCUMFLEX
+        ^ ASCII_DIAERESIS
+        ^ ASCII_ROUGH
+        ^ ASCII_SMOOTH
+        ^ ASCII_IOTA
+        ^ ASCII_SIGMA1
+        ^ ASCII_SIGMA2
+        ^ ASCII_SIGMA3
+        ^ ASCII_MACRON
+        ^ ASCII_BREVE)
+        == Accents::all().bits()
+);
+const _: () = assert!(Accents::all().bits().count_ones() == 12);
+const _: () = assert!(Accents::all().bits() & ASCII_REPEATED == 0);
+const _: () = assert!((Accents::all().bits() | ASCII_REPEATED) & ASCII_UNDER_DOT == 0);
+
+const ASCII_SMOOTH_ACUTE: u16 = ASCII_SMOOTH + ASCII_ACUTE;
+const ASCII_SMOOTH_GRAVE: u16 = ASCII_SMOOTH + ASCII_GRAVE;
+const ASCII_ROUGH_ACUTE: u16 = ASCII_ROUGH + ASCII_ACUTE;
+const ASCII_ROUGH_GRAVE: u16 = ASCII_ROUGH + ASCII_GRAVE;
+const ASCII_CIRCUMFLEX_ROUGH: u16 = ASCII_ROUGH + ASCII_CIRCUMFLEX;
+const ASCII_CIRCUMFLEX_SMOOTH: u16 = ASCII_SMOOTH + ASCII_CIRCUMFLEX;
+const ASCII_DIAERESIS_ACUTE: u16 = ASCII_DIAERESIS + ASCII_ACUTE;
+const ASCII_DIAERESIS_GRAVE: u16 = ASCII_DIAERESIS + ASCII_GRAVE;
+const ASCII_DIAERESIS_CIRCUMFLEX: u16 = ASCII_DIAERESIS + ASCII_CIRCUMFLEX;
+const ASCII_IOTA_SMOOTH: u16 = ASCII_IOTA + ASCII_SMOOTH;
+const ASCII_IOTA_ROUGH: u16 = ASCII_IOTA + ASCII_ROUGH;
+const ASCII_IOTA_ACUTE: u16 = ASCII_IOTA + ASCII_ACUTE;
+const ASCII_IOTA_GRAVE: u16 = ASCII_IOTA + ASCII_GRAVE;
+const ASCII_IOTA_CIRCUMFLEX: u16 = ASCII_IOTA + ASCII_CIRCUMFLEX;
+const ASCII_IOTA_SMOOTH_ACUTE: u16 = ASCII_IOTA + ASCII_SMOOTH_ACUTE;
+const ASCII_IOTA_SMOOTH_GRAVE: u16 = ASCII_IOTA + ASCII_SMOOTH_GRAVE;
+const ASCII_IOTA_ROUGH_ACUTE: u16 = ASCII_IOTA + ASCII_ROUGH_ACUTE;
+const ASCII_IOTA_ROUGH_GRAVE: u16 = ASCII_IOTA + ASCII_ROUGH_GRAVE;
+const ASCII_IOTA_CIRCUMFLEX_SMOOTH: u16 = ASCII_IOTA + ASCII_CIRCUMFLEX_SMOOTH;
+const ASCII_IOTA_CIRCUMFLEX_ROUGH: u16 = ASCII_IOTA + ASCII_CIRCUMFLEX_ROUGH;
+
+#[inline]
+fn is_ascii_whitespace(c: u8) -> bool {
+    c == b' ' || c == b'\r' || c == b'\n' || c == b'\t'
+}
+
+// Return true if betacode written after whitespace and starting with this
+// byte is a new word. An apostrophe after whitespace may be the elision
+// mark of the word before it, and punctuation may end its clause, so they
+// are converted along with that word.
+#[inline]
+fn starts_word(c: u8) -> bool {
+    !is_ascii_whitespace(c) && c != b'\'' && lookup_punctuation(c) == 0 as char
+}
+
+// Convert Greek into betacode, written into the output.
+fn convert_greek<O: Output>(
+    input: &str,
+    version: Type,
+    betacode: &mut O,
+) -> Result<(), ConversionError> {
+    let mut current = Letter {
+        c: 0 as char,
+        accents: 0,
+        index: 0,
+        final_sigma: false,
+    };
+    for (i, c) in input.char_indices() {
+        match decompose(c) {
+            Some(d) => {
+                for e in d.chars() {
+                    push_greek(betacode, &mut current, e, i, version)?;
+                }
+            }
+            None => push_greek(betacode, &mut current, c, i, version)?,
+        }
+    }
+    push_betacode_letter(betacode, &mut current, true, version)
+}
+
+// Read one Greek character, after decomposition, into betacode. A letter
+// waits in `current` until the following character shows whether it is the
+// last letter in a word.
+fn push_greek<O: Output>(
+    betacode: &mut O,
+    current: &mut Letter,
+    c: char,
+    index: usize,
+    version: Type,
+) -> Result<(), ConversionError> {
+    let accent = lookup_combining_accent(c);
+    if accent != 0 {
+        if current.c == 0 as char {
+            // A diacritic with no letter before it
+            return Err(ConversionError::UnexpectedCharacter(c, index));
+        }
+        current.accents |= accent;
+        return Ok(());
+    }
+    let letter = is_sigma(c) || lookup_betacode_letter(c, version) != 0;
+    // The numeral sign follows a letter without ending the word.
+    let numeral = c == '\u{2b9}' || c == '\u{374}';
+    push_betacode_letter(betacode, current, !letter && !numeral, version)?;
+    if letter {
+        *current = Letter {
+            c,
+            accents: 0,
+            index,
+            final_sigma: false,
+        };
+        return Ok(());
+    }
+    let p = match c {
+        ' ' | '\t' | '\r' | '\n' => c,
+        ',' | '.' => c,
+        '\u{387}' | '\u{b7}' => ':',
+        '\u{37e}' | ';' => ';',
+        // An elided word may be written with the Greek elision mark, or
+        // with an ascii apostrophe or a right single quote.
+        '᾽' | '\'' | '\u{2019}' => '\'',
+        '\u{2b9}' | '\u{374}' | '\u{375}' => '#',
+        '\u{2014}' | '\u{2015}' => '_',
+        _ => return Err(ConversionError::UnexpectedCharacter(c, index)),
+    };
+    betacode.push(p);
+    Ok(())
+}
+
+// Write the waiting Greek letter, if there is one, as betacode. Breathings
+// and accents of a TLG capital letter are written before the letter.
+fn push_betacode_letter<O: Output>(
+    betacode: &mut O,
+    letter: &mut Letter,
+    word_end: bool,
+    version: Type,
+) -> Result<(), ConversionError> {
+    let c = letter.c;
+    if c == 0 as char {
+        return Ok(());
+    }
+    letter.c = 0 as char;
+    let mut accents = letter.accents;
+    let base = if is_sigma(c) {
+        if accents != 0 {
+            return Err(ConversionError::UnexpectedAccent(c, letter.index));
+        }
+        // Write the sigma number whenever the sigma would otherwise
+        // be read back as a different form of sigma.
+        match c {
+            'σ' if word_end => accents = ASCII_SIGMA1,
+            'ς' if !word_end => accents = ASCII_SIGMA2,
+            'ϲ' | 'Ϲ' => accents = ASCII_SIGMA3,
+            _ => {}
+        }
+        if c == 'Σ' || c == 'Ϲ' {
+            b'S'
+        } else {
+            b's'
+        }
+    } else {
+        let mut check = Letter {
+            c,
+            accents,
+            index: letter.index,
+            final_sigma: false,
+        };
+        push_letter(
+            &mut Discard { empty: true },
+            &Converter::new(version),
+            &mut check,
+            false,
+            &mut Err,
+        )?;
+        lookup_betacode_letter(c, version)
+    };
+
+    if version.asterix_capitals() && base.is_ascii_uppercase() {
+        betacode.push('*');
+        let after = ASCII_LENGTH | ASCII_IOTA | ASCII_SIGMA;
+        push_betacode_accents(betacode, accents & !after);
+        betacode.push(base.to_ascii_lowercase() as char);
+        push_betacode_accents(betacode, accents & after);
+    } else if version.asterix_capitals() && base == b'#' {
+        // Archaic letters are written as a hash and a number
+        for n in 1..=5 {
+            if lookup_numbered_letter(n, false) == c {
+                betacode.push('#');
+                betacode.push((b'0' + n as u8) as char);
+            }
+            if lookup_numbered_letter(n, true) == c {
+                betacode.push('*');
+                betacode.push('#');
+                betacode.push((b'0' + n as u8) as char);
+            }
+        }
+    } else {
+        betacode.push(base as char);
+        push_betacode_accents(betacode, accents);
+    }
+    Ok(())
+}
+
+// Write betacode accent symbols, in the order breathing, diaeresis, accent,
+// length mark, iota subscript and then sigma number.
+fn push_betacode_accents<O: Output>(betacode: &mut O, accents: u16) {
+    for (bit, symbol) in [
+        (ASCII_SMOOTH, ")"),
+        (ASCII_ROUGH, "("),
+        (ASCII_DIAERESIS, "+"),
+        (ASCII_ACUTE, "/"),
+        (ASCII_GRAVE, "\\"),
+        (ASCII_CIRCUMFLEX, "="),
+        (ASCII_MACRON, "%27"),
+        (ASCII_BREVE, "%26"),
+        (ASCII_IOTA, "|"),
+        (ASCII_SIGMA1, "1"),
+        (ASCII_SIGMA2, "2"),
+        (ASCII_SIGMA3, "3"),
+    ] {
+        if accents & bit != 0 {
+            for c in symbol.chars() {
+                betacode.push(c);
+            }
+        }
+    }
+}
+
+#[inline]
+fn is_sigma(c: char) -> bool {
+    matches!(c, 'σ' | 'ς' | 'ϲ' | 'Σ' | 'Ϲ')
+}
+
+// Return the betacode letter that reads as a Greek letter, or zero if there
+// is none. Searching the letter table keeps both directions of conversion
+// in agreement. Lowercase ascii letters are preferred. A TLG archaic letter
+// returns a hash.
+fn lookup_betacode_letter(c: char, version: Type) -> u8 {
+    for b in (b'a'..=b'z').chain(b'A'..=b'Z') {
+        if lookup_greek_letter(b, version) == c {
+            return b;
+        }
+    }
+    if version.asterix_capitals() {
+        for n in 1..=5 {
+            if lookup_numbered_letter(n, false) == c || lookup_numbered_letter(n, true) == c {
+                return b'#';
+            }
+        }
+    }
+    0
+}
+
+// Return the betacode accent for a Greek combining diacritic, or zero if
+// the character is not one.
+#[inline]
+fn lookup_combining_accent(c: char) -> u16 {
+    match c {
+        '\u{301}' => ASCII_ACUTE,
+        '\u{300}' => ASCII_GRAVE,
+        '\u{342}' => ASCII_CIRCUMFLEX,
+        '\u{308}' => ASCII_DIAERESIS,
+        '\u{314}' => ASCII_ROUGH,
+        '\u{313}' => ASCII_SMOOTH,
+        '\u{345}' => ASCII_IOTA,
+        '\u{304}' => ASCII_MACRON,
+        '\u{306}' => ASCII_BREVE,
+        _ => 0,
+    }
+}
+
+// Return the Greek letter an ascii byte reads as, or zero if it is not a
+// letter in this betacode type.
+#[inline]
+fn lookup_greek_letter(c: u8, version: Type) -> char {
+    match LETTERS[version as usize].get(c as usize) {
+        Some(&l) => l,
+        None => 0 as char,
+    }
+}
+
+// The Greek letter for each ascii byte, one table for each betacode type,
+// indexed by the type. The tables are built at compile time, so converting
+// a letter is a single index rather than a match.
+static LETTERS: [[char; 128]; 3] = [
+    letter_table(Type::Default),
+    letter_table(Type::TLG),
+    letter_table(Type::Perseus),
+];
+
+const fn letter_table(version: Type) -> [char; 128] {
+    let mut table = [0 as char; 128];
+    let mut c = 0;
+    while c < 128 {
+        table[c] = greek_letter_of(c as u8, version);
+        c += 1;
+    }
+    table
+}
+
+const fn greek_letter_of(c: u8, version: Type) -> char {
+    let o = match c {
+        b'a' => 'α',
+        b'b' => 'β',
+        b'd' => 'δ',
+        b'e' => 'ε',
+        b'f' => 'φ',
+        b'g' => 'γ',
+        b'h' => 'η',
+        b'i' => 'ι',
+        b'k' => 'κ',
+        b'l' => 'λ',
+        b'm' => 'μ',
+        b'n' => 'ν',
+        b'o' => 'ο',
+        b'p' => 'π',
+        b'q' => 'θ',
+        b'r' => 'ρ',
+        b's' => 'σ',
+        b't' => 'τ',
+        b'u' => 'υ',
+        b'w' => 'ω',
+        b'y' => 'ψ',
+        b'z' => 'ζ',
+        b'A' => 'Α',
+        b'B' => 'Β',
+        b'D' => 'Δ',
+        b'E' => 'Ε',
+        b'F' => 'Φ',
+        b'G' => 'Γ',
+        b'H' => 'Η',
+        b'I' => 'Ι',
+        b'K' => 'Κ',
+        b'L' => 'Λ',
+        b'M' => 'Μ',
+        b'N' => 'Ν',
+        b'O' => 'Ο',
+        b'P' => 'Π',
+        b'Q' => 'Θ',
+        b'R' => 'Ρ',
+        b'S' => 'Σ',
+        b'T' => 'Τ',
+        b'U' => 'Υ',
+        b'W' => 'Ω',
+        b'Y' => 'Ψ',
+        b'Z' => 'Ζ',
+        _ => 0 as char,
+    };
+    if o != 0 as char {
+        return o;
+    }
+
+    match version {
+        // Who uses these mpapings
+        Type::Default => {
+            let o = match c {
+                // v is always a final sigma, wherever it appears in a
+                // word, unless a sigma number or the sigma policy says
+                // otherwise. A capital sigma has no final form.
+                b'v' => 'σ',
+                b'V' => 'Σ',
+                // Some betacode systems use j for sigma. It is written
+                // by the same rules as s, so the sigma policy applies.
+                b'j' => 'σ',
+                b'J' => 'Σ',
+                // Chi is c, as x is not a letter in default betacode.
+                b'c' => 'χ',
+                b'C' => 'Χ',
+                _ => 0 as char,
+            };
+            if o != 0 as char {
+                return o;
+            }
+        }
+        Type::TLG => {
+            let o = match c {
+                b'v' => 'ϝ',
+                b'V' => 'Ϝ',
+                b'c' => 'ξ',
+                b'C' => 'Ξ',
+                b'x' => 'χ',
+                b'X' => 'Χ',
+                _ => 0 as char,
+            };
+            if o != 0 as char {
+                return o;
+            }
+        }
+        Type::Perseus => {
+            let o = match c {
+                b'v' => 'ϝ',
+                b'V' => 'Ϝ',
+                b'c' => 'ξ',
+                b'C' => 'Ξ',
+                b'x' => 'χ',
+                b'X' => 'Χ',
+                // Perseus texts write a final sigma as j. It is written
+                // by the same rules as s, so the sigma policy applies.
+                b'j' => 'σ',
+                b'J' => 'Σ',
+                _ => 0 as char,
+            };
+            if o != 0 as char {
+                return o;
+            }
+        }
+    }
+
+    0 as char
+}
+
+fn apply_accent(c: char, accents: u16) -> char {
+    if accents == 0 {
+        return c;
+    }
+
+    match ACCENTS.binary_search_by(|&(l, a, _)| (l, a).cmp(&(c, accents))) {
+        Ok(found) => ACCENTS[found].2,
+        Err(_) => 0 as char,
+    }
+}
+
+// Return the precomposed vowel with a macron or a breve, or zero if there
+// is none. Only alpha, iota and upsilon may be either long or short.
+fn apply_length(c: char, length: u16) -> char {
+    match (c, length) {
+        ('α', ASCII_MACRON) => 'ᾱ',
+        ('α', ASCII_BREVE) => 'ᾰ',
+        ('ι', ASCII_MACRON) => 'ῑ',
+        ('ι', ASCII_BREVE) => 'ῐ',
+        ('υ', ASCII_MACRON) => 'ῡ',
+        ('υ', ASCII_BREVE) => 'ῠ',
+        ('Α', ASCII_MACRON) => 'Ᾱ',
+        ('Α', ASCII_BREVE) => 'Ᾰ',
+        ('Ι', ASCII_MACRON) => 'Ῑ',
+        ('Ι', ASCII_BREVE) => 'Ῐ',
+        ('Υ', ASCII_MACRON) => 'Ῡ',
+        ('Υ', ASCII_BREVE) => 'Ῠ',
+        (_, _) => 0 as char,
+    }
+}
+
+// Each letter with a set of accents, and the precomposed character written
+// for it. Sorted by letter then accents so it can be binary searched.
+static ACCENTS: &[(char, u16, char)] = &[
+    ('Α', ASCII_ACUTE, 'Ά'),
+    ('Α', ASCII_GRAVE, 'Ὰ'),
+    ('Α', ASCII_ROUGH, 'Ἁ'),
+    ('Α', ASCII_ROUGH_ACUTE, 'Ἅ'),
+    ('Α', ASCII_ROUGH_GRAVE, 'Ἃ'),
+    ('Α', ASCII_CIRCUMFLEX_ROUGH, 'Ἇ'),
+    ('Α', ASCII_SMOOTH, 'Ἀ'),
+    ('Α', ASCII_SMOOTH_ACUTE, 'Ἄ'),
+    ('Α', ASCII_SMOOTH_GRAVE, 'Ἂ'),
+    ('Α', ASCII_CIRCUMFLEX_SMOOTH, 'Ἆ'),
+    ('Α', ASCII_IOTA, 'ᾼ'),
+    ('Α', ASCII_IOTA_ROUGH, 'ᾉ'),
+    ('Α', ASCII_IOTA_ROUGH_ACUTE, 'ᾍ'),
+    ('Α', ASCII_IOTA_ROUGH_GRAVE, 'ᾋ'),
+    ('Α', ASCII_IOTA_CIRCUMFLEX_ROUGH, 'ᾏ'),
+    ('Α', ASCII_IOTA_SMOOTH, 'ᾈ'),
+    ('Α', ASCII_IOTA_SMOOTH_ACUTE, 'ᾌ'),
+    ('Α', ASCII_IOTA_SMOOTH_GRAVE, 'ᾊ'),
+    ('Α', ASCII_IOTA_CIRCUMFLEX_SMOOTH, 'ᾎ'),
+    ('Ε', ASCII_ACUTE, 'Έ'),
+    ('Ε', ASCII_GRAVE, 'Ὲ'),
+    ('Ε', ASCII_ROUGH, 'Ἑ'),
+    ('Ε', ASCII_ROUGH_ACUTE, 'Ἕ'),
+    ('Ε', ASCII_ROUGH_GRAVE, 'Ἓ'),
+    ('Ε', ASCII_SMOOTH, 'Ἐ'),
+    ('Ε', ASCII_SMOOTH_ACUTE, 'Ἔ'),
+    ('Ε', ASCII_SMOOTH_GRAVE, 'Ἒ'),
+    ('Η', ASCII_ACUTE, 'Ή'),
+    ('Η', ASCII_GRAVE, 'Ὴ'),
+    ('Η', ASCII_ROUGH, 'Ἡ'),
+    ('Η', ASCII_ROUGH_ACUTE, 'Ἥ'),
+    ('Η', ASCII_ROUGH_GRAVE, 'Ἣ'),
+    ('Η', ASCII_CIRCUMFLEX_ROUGH, 'Ἧ'),
+    ('Η', ASCII_SMOOTH, 'Ἠ'),
+    ('Η', ASCII_SMOOTH_ACUTE, 'Ἤ'),
+    ('Η', ASCII_SMOOTH_GRAVE, 'Ἢ'),
+    ('Η', ASCII_CIRCUMFLEX_SMOOTH, 'Ἦ'),
+    ('Η', ASCII_IOTA, 'ῌ'),
+    ('Η', ASCII_IOTA_ROUGH, 'ᾙ'),
+    ('Η', ASCII_IOTA_ROUGH_ACUTE, 'ᾝ'),
+    ('Η', ASCII_IOTA_ROUGH_GRAVE, 'ᾛ'),
+    ('Η', ASCII_IOTA_CIRCUMFLEX_ROUGH, 'ᾟ'),
+    ('Η', ASCII_IOTA_SMOOTH, 'ᾘ'),
+    ('Η', ASCII_IOTA_SMOOTH_ACUTE, 'ᾜ'),
+    ('Η', ASCII_IOTA_SMOOTH_GRAVE, 'ᾚ'),
+    ('Η', ASCII_IOTA_CIRCUMFLEX_SMOOTH, 'ᾞ'),
+    ('Ι', ASCII_ACUTE, 'Ί'),
+    ('Ι', ASCII_GRAVE, 'Ὶ'),
+    ('Ι', ASCII_DIAERESIS, 'Ϊ'),
+    ('Ι', ASCII_ROUGH, 'Ἱ'),
+    ('Ι', ASCII_ROUGH_ACUTE, 'Ἵ'),
+    ('Ι', ASCII_ROUGH_GRAVE, 'Ἳ'),
+    ('Ι', ASCII_CIRCUMFLEX_ROUGH, 'Ἷ'),
+    ('Ι', ASCII_SMOOTH, 'Ἰ'),
+    ('Ι', ASCII_SMOOTH_ACUTE, 'Ἴ'),
+    ('Ι', ASCII_SMOOTH_GRAVE, 'Ἲ'),
+    ('Ι', ASCII_CIRCUMFLEX_SMOOTH, 'Ἶ'),
+    ('Ο', ASCII_ACUTE, 'Ό'),
+    ('Ο', ASCII_GRAVE, 'Ὸ'),
+    ('Ο', ASCII_ROUGH, 'Ὁ'),
+    ('Ο', ASCII_ROUGH_ACUTE, 'Ὅ'),
+    ('Ο', ASCII_ROUGH_GRAVE, 'Ὃ'),
+    ('Ο', ASCII_SMOOTH, 'Ὀ'),
+    ('Ο', ASCII_SMOOTH_ACUTE, 'Ὄ'),
+    ('Ο', ASCII_SMOOTH_GRAVE, 'Ὂ'),
+    ('Ρ', ASCII_ROUGH, 'Ῥ'),
+    ('Σ', ASCII_SIGMA1, 'Σ'),
+    ('Σ', ASCII_SIGMA2, 'Σ'),
+    ('Σ', ASCII_SIGMA3, 'Ϲ'),
+    ('Υ', ASCII_ACUTE, 'Ύ'),
+    ('Υ', ASCII_GRAVE, 'Ὺ'),
+    ('Υ', ASCII_DIAERESIS, 'Ϋ'),
+    ('Υ', ASCII_ROUGH, 'Ὑ'),
+    ('Υ', ASCII_ROUGH_ACUTE, 'Ὕ'),
+    ('Υ', ASCII_ROUGH_GRAVE, 'Ὓ'),
+    ('Υ', ASCII_CIRCUMFLEX_ROUGH, 'Ὗ'),
+    ('Ω', ASCII_ACUTE, 'Ώ'),
+    ('Ω', ASCII_GRAVE, 'Ὼ'),
+    ('Ω', ASCII_ROUGH, 'Ὡ'),
+    ('Ω', ASCII_ROUGH_ACUTE, 'Ὥ'),
+    ('Ω', ASCII_ROUGH_GRAVE, 'Ὣ'),
+    ('Ω', ASCII_CIRCUMFLEX_ROUGH, 'Ὧ'),
+    ('Ω', ASCII_SMOOTH, 'Ὠ'),
+    ('Ω', ASCII_SMOOTH_ACUTE, 'Ὤ'),
+    ('Ω', ASCII_SMOOTH_GRAVE, 'Ὢ'),
+    ('Ω', ASCII_CIRCUMFLEX_SMOOTH, 'Ὦ'),
+    ('Ω', ASCII_IOTA, 'ῼ'),
+    ('Ω', ASCII_IOTA_ROUGH, 'ᾩ'),
+    ('Ω', ASCII_IOTA_ROUGH_ACUTE, 'ᾭ'),
+    ('Ω', ASCII_IOTA_ROUGH_GRAVE, 'ᾫ'),
+    ('Ω', ASCII_IOTA_CIRCUMFLEX_ROUGH, 'ᾯ'),
+    ('Ω', ASCII_IOTA_SMOOTH, 'ᾨ'),
+    ('Ω', ASCII_IOTA_SMOOTH_ACUTE, 'ᾬ'),
+    ('Ω', ASCII_IOTA_SMOOTH_GRAVE, 'ᾪ'),
+    ('Ω', ASCII_IOTA_CIRCUMFLEX_SMOOTH, 'ᾮ'),
+    ('α', ASCII_ACUTE, 'ά'),
+    ('α', ASCII_GRAVE, 'ὰ'),
+    ('α', ASCII_CIRCUMFLEX, 'ᾶ'),
+    ('α', ASCII_ROUGH, 'ἁ'),
+    ('α', ASCII_ROUGH_ACUTE, 'ἅ'),
+    ('α', ASCII_ROUGH_GRAVE, 'ἃ'),
+    ('α', ASCII_CIRCUMFLEX_ROUGH, 'ἇ'),
+    ('α', ASCII_SMOOTH, 'ἀ'),
+    ('α', ASCII_SMOOTH_ACUTE, 'ἄ'),
+    ('α', ASCII_SMOOTH_GRAVE, 'ἂ'),
+    ('α', ASCII_CIRCUMFLEX_SMOOTH, 'ἆ'),
+    ('α', ASCII_IOTA, 'ᾳ'),
+    ('α', ASCII_IOTA_ACUTE, 'ᾴ'),
+    ('α', ASCII_IOTA_GRAVE, 'ᾲ'),
+    ('α', ASCII_IOTA_CIRCUMFLEX, 'ᾷ'),
+    ('α', ASCII_IOTA_ROUGH, 'ᾁ'),
+    ('α', ASCII_IOTA_ROUGH_ACUTE, 'ᾅ'),
+    ('α', ASCII_IOTA_ROUGH_GRAVE, 'ᾃ'),
+    ('α', ASCII_IOTA_CIRCUMFLEX_ROUGH, 'ᾇ'),
+    ('α', ASCII_IOTA_SMOOTH, 'ᾀ'),
+    ('α', ASCII_IOTA_SMOOTH_ACUTE, 'ᾄ'),
+    ('α', ASCII_IOTA_SMOOTH_GRAVE, 'ᾂ'),
+    ('α', ASCII_IOTA_CIRCUMFLEX_SMOOTH, 'ᾆ'),
+    ('ε', ASCII_ACUTE, 'έ'),
+    ('ε', ASCII_GRAVE, 'ὲ'),
+    ('ε', ASCII_ROUGH, 'ἑ'),
+    ('ε', ASCII_ROUGH_ACUTE, 'ἕ'),
+    ('ε', ASCII_ROUGH_GRAVE, 'ἓ'),
+    ('ε', ASCII_SMOOTH, 'ἐ'),
+    ('ε', ASCII_SMOOTH_ACUTE, 'ἔ'),
+    ('ε', ASCII_SMOOTH_GRAVE, 'ἒ'),
+    ('η', ASCII_ACUTE, 'ή'),
+    ('η', ASCII_GRAVE, 'ὴ'),
+    ('η', ASCII_CIRCUMFLEX, 'ῆ'),
+    ('η', ASCII_ROUGH, 'ἡ'),
+    ('η', ASCII_ROUGH_ACUTE, 'ἥ'),
+    ('η', ASCII_ROUGH_GRAVE, 'ἣ'),
+    ('η', ASCII_CIRCUMFLEX_ROUGH, 'ἧ'),
+    ('η', ASCII_SMOOTH, 'ἠ'),
+    ('η', ASCII_SMOOTH_ACUTE, 'ἤ'),
+    ('η', ASCII_SMOOTH_GRAVE, 'ἢ'),
+    ('η', ASCII_CIRCUMFLEX_SMOOTH, 'ἦ'),
+    ('η', ASCII_IOTA, 'ῃ'),
+    ('η', ASCII_IOTA_ACUTE, 'ῄ'),
+    ('η', ASCII_IOTA_GRAVE, 'ῂ'),
+    ('η', ASCII_IOTA_CIRCUMFLEX, 'ῇ'),
+    ('η', ASCII_IOTA_ROUGH, 'ᾑ'),
+    ('η', ASCII_IOTA_ROUGH_ACUTE, 'ᾕ'),
+    ('η', ASCII_IOTA_ROUGH_GRAVE, 'ᾓ'),
+    ('η', ASCII_IOTA_CIRCUMFLEX_ROUGH, 'ᾗ'),
+    ('η', ASCII_IOTA_SMOOTH, 'ᾐ'),
+    ('η', ASCII_IOTA_SMOOTH_ACUTE, 'ᾔ'),
+    ('η', ASCII_IOTA_SMOOTH_GRAVE, 'ᾒ'),
+    ('η', ASCII_IOTA_CIRCUMFLEX_SMOOTH, 'ᾖ'),
+    ('ι', ASCII_ACUTE, 'ί'),
+    ('ι', ASCII_GRAVE, 'ὶ'),
+    ('ι', ASCII_CIRCUMFLEX, 'ῖ'),
+    ('ι', ASCII_DIAERESIS, 'ϊ'),
+    ('ι', ASCII_DIAERESIS_ACUTE, 'ΐ'),
+    ('ι', ASCII_DIAERESIS_GRAVE, 'ῒ'),
+    ('ι', ASCII_DIAERESIS_CIRCUMFLEX, 'ῗ'),
+    ('ι', ASCII_ROUGH, 'ἱ'),
+    ('ι', ASCII_ROUGH_ACUTE, 'ἵ'),
+    ('ι', ASCII_ROUGH_GRAVE, 'ἳ'),
+    ('ι', ASCII_CIRCUMFLEX_ROUGH, 'ἷ'),
+    ('ι', ASCII_SMOOTH, 'ἰ'),
+    ('ι', ASCII_SMOOTH_ACUTE, 'ἴ'),
+    ('ι', ASCII_SMOOTH_GRAVE, 'ἲ'),
+    ('ι', ASCII_CIRCUMFLEX_SMOOTH, 'ἶ'),
+    ('ο', ASCII_ACUTE, 'ό'),
+    ('ο', ASCII_GRAVE, 'ὸ'),
+    ('ο', ASCII_ROUGH, 'ὁ'),
+    ('ο', ASCII_ROUGH_ACUTE, 'ὅ'),
+    ('ο', ASCII_ROUGH_GRAVE, 'ὃ'),
+    ('ο', ASCII_SMOOTH, 'ὀ'),
+    ('ο', ASCII_SMOOTH_ACUTE, 'ὄ'),
+    ('ο', ASCII_SMOOTH_GRAVE, 'ὂ'),
+    ('ρ', ASCII_ROUGH, 'ῥ'),
+    ('ρ', ASCII_SMOOTH, 'ῤ'),
+    ('σ', ASCII_SIGMA1, 'σ'),
+    ('σ', ASCII_SIGMA2, 'ς'),
+    ('σ', ASCII_SIGMA3, 'ϲ'),
+    ('υ', ASCII_ACUTE, 'ύ'),
+    ('υ', ASCII_GRAVE, 'ὺ'),
+    ('υ', ASCII_CIRCUMFLEX, 'ῦ'),
+    ('υ', ASCII_DIAERESIS, 'ϋ'),
+    ('υ', ASCII_DIAERESIS_ACUTE, 'ΰ'),
+    ('υ', ASCII_DIAERESIS_GRAVE, 'ῢ'),
+    ('υ', ASCII_DIAERESIS_CIRCUMFLEX, 'ῧ'),
+    ('υ', ASCII_ROUGH, 'ὑ'),
+    ('υ', ASCII_ROUGH_ACUTE, 'ὕ'),
+    ('υ', ASCII_ROUGH_GRAVE, 'ὓ'),
+    ('υ', ASCII_CIRCUMFLEX_ROUGH, 'ὗ'),
+    ('υ', ASCII_SMOOTH, 'ὐ'),
+    ('υ', ASCII_SMOOTH_ACUTE, 'ὔ'),
+    ('υ', ASCII_SMOOTH_GRAVE, 'ὒ'),
+    ('υ', ASCII_CIRCUMFLEX_SMOOTH, 'ὖ'),
+    ('ω', ASCII_ACUTE, 'ώ'),
+    ('ω', ASCII_GRAVE, 'ὼ'),
+    ('ω', ASCII_CIRCUMFLEX, 'ῶ'),
+    ('ω', ASCII_ROUGH, 'ὡ'),
+    ('ω', ASCII_ROUGH_ACUTE, 'ὥ'),
+    ('ω', ASCII_ROUGH_GRAVE, 'ὣ'),
+    ('ω', ASCII_CIRCUMFLEX_ROUGH, 'ὧ'),
+    ('ω', ASCII_SMOOTH, 'ὠ'),
+    ('ω', ASCII_SMOOTH_ACUTE, 'ὤ'),
+    ('ω', ASCII_SMOOTH_GRAVE, 'ὢ'),
+    ('ω', ASCII_CIRCUMFLEX_SMOOTH, 'ὦ'),
+    ('ω', ASCII_IOTA, 'ῳ'),
+    ('ω', ASCII_IOTA_ACUTE, 'ῴ'),
+    ('ω', ASCII_IOTA_GRAVE, 'ῲ'),
+    ('ω', ASCII_IOTA_CIRCUMFLEX, 'ῷ'),
+    ('ω', ASCII_IOTA_ROUGH, 'ᾡ'),
+    ('ω', ASCII_IOTA_ROUGH_ACUTE, 'ᾥ'),
+    ('ω', ASCII_IOTA_ROUGH_GRAVE, 'ᾣ'),
+    ('ω', ASCII_IOTA_CIRCUMFLEX_ROUGH, 'ᾧ'),
+    ('ω', ASCII_IOTA_SMOOTH, 'ᾠ'),
+    ('ω', ASCII_IOTA_SMOOTH_ACUTE, 'ᾤ'),
+    ('ω', ASCII_IOTA_SMOOTH_GRAVE, 'ᾢ'),
+    ('ω', ASCII_IOTA_CIRCUMFLEX_SMOOTH, 'ᾦ'),
+];
+
+// Some accented capitals have no precomposed character in Unicode. These
+// are written as the capital letter followed by combining diacritics.
+fn apply_combining_accent(c: char, accents: u16) -> Option<&'static str> {
+    let d = match (c, accents) {
+        ('Υ', ASCII_SMOOTH) => "Υ\u{313}",
+        ('Υ', ASCII_SMOOTH_ACUTE) => "Υ\u{313}\u{301}",
+        ('Υ', ASCII_SMOOTH_GRAVE) => "Υ\u{313}\u{300}",
+        ('Υ', ASCII_CIRCUMFLEX_SMOOTH) => "Υ\u{313}\u{342}",
+        ('Ι', ASCII_DIAERESIS_ACUTE) => "Ι\u{308}\u{301}",
+        ('Ι', ASCII_DIAERESIS_GRAVE) => "Ι\u{308}\u{300}",
+        ('Υ', ASCII_DIAERESIS_ACUTE) => "Υ\u{308}\u{301}",
+        ('Υ', ASCII_DIAERESIS_GRAVE) => "Υ\u{308}\u{300}",
+        ('Ι', ASCII_DIAERESIS_CIRCUMFLEX) => "Ι\u{308}\u{342}",
+        ('Υ', ASCII_DIAERESIS_CIRCUMFLEX) => "Υ\u{308}\u{342}",
+        ('Α', ASCII_IOTA_ACUTE) => "Α\u{301}\u{345}",
+        ('Α', ASCII_IOTA_GRAVE) => "Α\u{300}\u{345}",
+        ('Α', ASCII_IOTA_CIRCUMFLEX) => "Α\u{342}\u{345}",
+        ('Η', ASCII_IOTA_ACUTE) => "Η\u{301}\u{345}",
+        ('Η', ASCII_IOTA_GRAVE) => "Η\u{300}\u{345}",
+        ('Η', ASCII_IOTA_CIRCUMFLEX) => "Η\u{342}\u{345}",
+        ('Ω', ASCII_IOTA_ACUTE) => "Ω\u{301}\u{345}",
+        ('Ω', ASCII_IOTA_GRAVE) => "Ω\u{300}\u{345}",
+        ('Ω', ASCII_IOTA_CIRCUMFLEX) => "Ω\u{342}\u{345}",
+        (_, _) => return None,
+    };
+    Some(d)
+}
+
+// Return true for a Greek vowel without diacritics.
+#[inline]
+fn is_vowel(c: char) -> bool {
+    matches!(
+        c,
+        'α' | 'ε' | 'η' | 'ι' | 'ο' | 'υ' | 'ω' | 'Α' | 'Ε' | 'Η' | 'Ι' | 'Ο' | 'Υ' | 'Ω'
+    )
+}
+
+// Return the canonical (NFD) decomposition of a Greek character, or None
+// if the character does not decompose.
+fn decompose(c: char) -> Option<&'static str> {
+    let d = match c {
+        '\u{340}' => "\u{300}",
+        '\u{341}' => "\u{301}",
+        '\u{343}' => "\u{313}",
+        '\u{344}' => "\u{308}\u{301}",
+        '\u{374}' => "\u{2b9}",
+        '\u{37e}' => "\u{3b}",
+        '\u{385}' => "\u{a8}\u{301}",
+        'Ά' => "Α\u{301}",
+        '\u{387}' => "\u{b7}",
+        'Έ' => "Ε\u{301}",
+        'Ή' => "Η\u{301}",
+        'Ί' => "Ι\u{301}",
+        'Ό' => "Ο\u{301}",
+        'Ύ' => "Υ\u{301}",
+        'Ώ' => "Ω\u{301}",
+        'ΐ' => "ι\u{308}\u{301}",
+        'Ϊ' => "Ι\u{308}",
+        'Ϋ' => "Υ\u{308}",
+        'ά' => "α\u{301}",
+        'έ' => "ε\u{301}",
+        'ή' => "η\u{301}",
+        'ί' => "ι\u{301}",
+        'ΰ' => "υ\u{308}\u{301}",
+        'ϊ' => "ι\u{308}",
+        'ϋ' => "υ\u{308}",
+        'ό' => "ο\u{301}",
+        'ύ' => "υ\u{301}",
+        'ώ' => "ω\u{301}",
+        'ϓ' => "ϒ\u{301}",
+        'ϔ' => "ϒ\u{308}",
+        'ἀ' => "α\u{313}",
+        'ἁ' => "α\u{314}",
+        'ἂ' => "α\u{313}\u{300}",
+        'ἃ' => "α\u{314}\u{300}",
+        'ἄ' => "α\u{313}\u{301}",
+        'ἅ' => "α\u{314}\u{301}",
+        'ἆ' => "α\u{313}\u{342}",
+        'ἇ' => "α\u{314}\u{342}",
+        'Ἀ' => "Α\u{313}",
+        'Ἁ' => "Α\u{314}",
+        'Ἂ' => "Α\u{313}\u{300}",
+        'Ἃ' => "Α\u{314}\u{300}",
+        'Ἄ' => "Α\u{313}\u{301}",
+        'Ἅ' => "Α\u{314}\u{301}",
+        'Ἆ' => "Α\u{313}\u{342}",
+        'Ἇ' => "Α\u{314}\u{342}",
+        'ἐ' => "ε\u{313}",
+        'ἑ' => "ε\u{314}",
+        'ἒ' => "ε\u{313}\u{300}",
+        'ἓ' => "ε\u{314}\u{300}",
+        'ἔ' => "ε\u{313}\u{301}",
+        'ἕ' => "ε\u{314}\u{301}",
+        'Ἐ' => "Ε\u{313}",
+        'Ἑ' => "Ε\u{314}",
+        'Ἒ' => "Ε\u{313}\u{300}",
+        'Ἓ' => "Ε\u{314}\u{300}",
+        'Ἔ' => "Ε\u{313}\u{301}",
+        'Ἕ' => "Ε\u{314}\u{301}",
+        'ἠ' => "η\u{313}",
+        'ἡ' => "η\u{314}",
+        'ἢ' => "η\u{313}\u{300}",
+        'ἣ' => "η\u{314}\u{300}",
+        'ἤ' => "η\u{313}\u{301}",
+        'ἥ' => "η\u{314}\u{301}",
+        'ἦ' => "η\u{313}\u{342}",
+        'ἧ' => "η\u{314}\u{342}",
+        'Ἠ' => "Η\u{313}",
+        'Ἡ' => "Η\u{314}",
+        'Ἢ' => "Η\u{313}\u{300}",
+        'Ἣ' => "Η\u{314}\u{300}",
+        'Ἤ' => "Η\u{313}\u{301}",
+        'Ἥ' => "Η\u{314}\u{301}",
+        'Ἦ' => "Η\u{313}\u{342}",
+        'Ἧ' => "Η\u{314}\u{342}",
+        'ἰ' => "ι\u{313}",
+        'ἱ' => "ι\u{314}",
+        'ἲ' => "ι\u{313}\u{300}",
+        'ἳ' => "ι\u{314}\u{300}",
+        'ἴ' => "ι\u{313}\u{301}",
+        'ἵ' => "ι\u{314}\u{301}",
+        'ἶ' => "ι\u{313}\u{342}",
+        'ἷ' => "ι\u{314}\u{342}",
+        'Ἰ' => "Ι\u{313}",
+        'Ἱ' => "Ι\u{314}",
+        'Ἲ' => "Ι\u{313}\u{300}",
+        'Ἳ' => "Ι\u{314}\u{300}",
+        'Ἴ' => "Ι\u{313}\u{301}",
+        'Ἵ' => "Ι\u{314}\u{301}",
+        'Ἶ' => "Ι\u{313}\u{342}",
+        'Ἷ' => "Ι\u{314}\u{342}",
+        'ὀ' => "ο\u{313}",
+        'ὁ' => "ο\u{314}",
+        'ὂ' => "ο\u{313}\u{300}",
+        'ὃ' => "ο\u{314}\u{300}",
+        'ὄ' => "ο\u{313}\u{301}",
+        'ὅ' => "ο\u{314}\u{301}",
+        'Ὀ' => "Ο\u{313}",
+        'Ὁ' => "Ο\u{314}",
+        'Ὂ' => "Ο\u{313}\u{300}",
+        'Ὃ' => "Ο\u{314}\u{300}",
+        'Ὄ' => "Ο\u{313}\u{301}",
+        'Ὅ' => "Ο\u{314}\u{301}",
+        'ὐ' => "υ\u{313}",
+        'ὑ' => "υ\u{314}",
+        'ὒ' => "υ\u{313}\u{300}",
+        'ὓ' => "υ\u{314}\u{300}",
+        'ὔ' => "υ\u{313}\u{301}",
+        'ὕ' => "υ\u{314}\u{301}",
+        'ὖ' => "υ\u{313}\u{342}",
+        'ὗ' => "υ\u{314}\u{342}",
+        'Ὑ' => "Υ\u{314}",
+        'Ὓ' => "Υ\u{314}\u{300}",
+        'Ὕ' => "Υ\u{314}\u{301}",
+        'Ὗ' => "Υ\u{314}\u{342}",
+        'ὠ' => "ω\u{313}",
+        'ὡ' => "ω\u{314}",
+        'ὢ' => "ω\u{313}\u{300}",
+        'ὣ' => "ω\u{314}\u{300}",
+        'ὤ' => "ω\u{313}\u{301}",
+        'ὥ' => "ω\u{314}\u{301}",
+        'ὦ' => "ω\u{313}\u{342}",
+        'ὧ' => "ω\u{314}\u{342}",
+        'Ὠ' => "Ω\u{313}",
+        'Ὡ' => "Ω\u{314}",
+        'Ὢ' => "Ω\u{313}\u{300}",
+        'Ὣ' => "Ω\u{314}\u{300}",
+        'Ὤ' => "Ω\u{313}\u{301}",
+        'Ὥ' => "Ω\u{314}\u{301}",
+        'Ὦ' => "Ω\u{313}\u{342}",
+        'Ὧ' => "Ω\u{314}\u{342}",
+        'ὰ' => "α\u{300}",
+        'ά' => "α\u{301}",
+        'ὲ' => "ε\u{300}",
+        'έ' => "ε\u{301}",
+        'ὴ' => "η\u{300}",
+        'ή' => "η\u{301}",
+        'ὶ' => "ι\u{300}",
+        'ί' => "ι\u{301}",
+        'ὸ' => "ο\u{300}",
+        'ό' => "ο\u{301}",
+        'ὺ' => "υ\u{300}",
+        'ύ' => "υ\u{301}",
+        'ὼ' => "ω\u{300}",
+        'ώ' => "ω\u{301}",
+        'ᾀ' => "α\u{313}\u{345}",
+        'ᾁ' => "α\u{314}\u{345}",
+        'ᾂ' => "α\u{313}\u{300}\u{345}",
+        'ᾃ' => "α\u{314}\u{300}\u{345}",
+        'ᾄ' => "α\u{313}\u{301}\u{345}",
+        'ᾅ' => "α\u{314}\u{301}\u{345}",
+        'ᾆ' => "α\u{313}\u{342}\u{345}",
+        'ᾇ' => "α\u{314}\u{342}\u{345}",
+        'ᾈ' => "Α\u{313}\u{345}",
+        'ᾉ' => "Α\u{314}\u{345}",
+        'ᾊ' => "Α\u{313}\u{300}\u{345}",
+        'ᾋ' => "Α\u{314}\u{300}\u{345}",
+        'ᾌ' => "Α\u{313}\u{301}\u{345}",
+        'ᾍ' => "Α\u{314}\u{301}\u{345}",
+        'ᾎ' => "Α\u{313}\u{342}\u{345}",
+        'ᾏ' => "Α\u{314}\u{342}\u{345}",
+        'ᾐ' => "η\u{313}\u{345}",
+        'ᾑ' => "η\u{314}\u{345}",
+        'ᾒ' => "η\u{313}\u{300}\u{345}",
+        'ᾓ' => "η\u{314}\u{300}\u{345}",
+        'ᾔ' => "η\u{313}\u{301}\u{345}",
+        'ᾕ' => "η\u{314}\u{301}\u{345}",
+        'ᾖ' => "η\u{313}\u{342}\u{345}",
+        'ᾗ' => "η\u{314}\u{342}\u{345}",
+        'ᾘ' => "Η\u{313}\u{345}",
+        'ᾙ' => "Η\u{314}\u{345}",
+        'ᾚ' => "Η\u{313}\u{300}\u{345}",
+        'ᾛ' => "Η\u{314}\u{300}\u{345}",
+        'ᾜ' => "Η\u{313}\u{301}\u{345}",
+        'ᾝ' => "Η\u{314}\u{301}\u{345}",
+        'ᾞ' => "Η\u{313}\u{342}\u{345}",
+        'ᾟ' => "Η\u{314}\u{342}\u{345}",
+        'ᾠ' => "ω\u{313}\u{345}",
+        'ᾡ' => "ω\u{314}\u{345}",
+        'ᾢ' => "ω\u{313}\u{300}\u{345}",
+        'ᾣ' => "ω\u{314}\u{300}\u{345}",
+        'ᾤ' => "ω\u{313}\u{301}\u{345}",
+        'ᾥ' => "ω\u{314}\u{301}\u{345}",
+        'ᾦ' => "ω\u{313}\u{342}\u{345}",
+        'ᾧ' => "ω\u{314}\u{342}\u{345}",
+        'ᾨ' => "Ω\u{313}\u{345}",
+        'ᾩ' => "Ω\u{314}\u{345}",
+        'ᾪ' => "Ω\u{313}\u{300}\u{345}",
+        'ᾫ' => "Ω\u{314}\u{300}\u{345}",
+        'ᾬ' => "Ω\u{313}\u{301}\u{345}",
+        'ᾭ' => "Ω\u{314}\u{301}\u{345}",
+        'ᾮ' => "Ω\u{313}\u{342}\u{345}",
+        'ᾯ' => "Ω\u{314}\u{342}\u{345}",
+        'ᾰ' => "α\u{306}",
+        'ᾱ' => "α\u{304}",
+        'ᾲ' => "α\u{300}\u{345}",
+        'ᾳ' => "α\u{345}",
+        'ᾴ' => "α\u{301}\u{345}",
+        'ᾶ' => "α\u{342}",
+        'ᾷ' => "α\u{342}\u{345}",
+        'Ᾰ' => "Α\u{306}",
+        'Ᾱ' => "Α\u{304}",
+        'Ὰ' => "Α\u{300}",
+        'Ά' => "Α\u{301}",
+        'ᾼ' => "Α\u{345}",
+        'ι' => "ι",
+        '\u{1fc1}' => "\u{a8}\u{342}",
+        'ῂ' => "η\u{300}\u{345}",
+        'ῃ' => "η\u{345}",
+        'ῄ' => "η\u{301}\u{345}",
+        'ῆ' => "η\u{342}",
+        'ῇ' => "η\u{342}\u{345}",
+        'Ὲ' => "Ε\u{300}",
+        'Έ' => "Ε\u{301}",
+        'Ὴ' => "Η\u{300}",
+        'Ή' => "Η\u{301}",
+        'ῌ' => "Η\u{345}",
+        '\u{1fcd}' => "\u{1fbf}\u{300}",
+        '\u{1fce}' => "\u{1fbf}\u{301}",
+        '\u{1fcf}' => "\u{1fbf}\u{342}",
+        'ῐ' => "ι\u{306}",
+        'ῑ' => "ι\u{304}",
+        'ῒ' => "ι\u{308}\u{300}",
+        'ΐ' => "ι\u{308}\u{301}",
+        'ῖ' => "ι\u{342}",
+        'ῗ' => "ι\u{308}\u{342}",
+        'Ῐ' => "Ι\u{306}",
+        'Ῑ' => "Ι\u{304}",
+        'Ὶ' => "Ι\u{300}",
+        'Ί' => "Ι\u{301}",
+        '\u{1fdd}' => "\u{1ffe}\u{300}",
+        '\u{1fde}' => "\u{1ffe}\u{301}",
+        '\u{1fdf}' => "\u{1ffe}\u{342}",
+        'ῠ' => "υ\u{306}",
+        'ῡ' => "υ\u{304}",
+        'ῢ' => "υ\u{308}\u{300}",
+        'ΰ' => "υ\u{308}\u{301}",
+        'ῤ' => "ρ\u{313}",
+        'ῥ' => "ρ\u{314}",
+        'ῦ' => "υ\u{342}",
+        'ῧ' => "υ\u{308}\u{342}",
+        'Ῠ' => "Υ\u{306}",
+        'Ῡ' => "Υ\u{304}",
+        'Ὺ' => "Υ\u{300}",
+        'Ύ' => "Υ\u{301}",
+        'Ῥ' => "Ρ\u{314}",
+        '\u{1fed}' => "\u{a8}\u{300}",
+        '\u{1fee}' => "\u{a8}\u{301}",
+        '\u{1fef}' => "\u{60}",
+        'ῲ' => "ω\u{300}\u{345}",
+        'ῳ' => "ω\u{345}",
+        'ῴ' => "ω\u{301}\u{345}",
+        'ῶ' => "ω\u{342}",
+        'ῷ' => "ω\u{342}\u{345}",
+        'Ὸ' => "Ο\u{300}",
+        'Ό' => "Ο\u{301}",
+        'Ὼ' => "Ω\u{300}",
+        'Ώ' => "Ω\u{301}",
+        'ῼ' => "Ω\u{345}",
+        '\u{1ffd}' => "\u{b4}",
+        _ => return None,
+    };
+    Some(d)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::{format, vec};
+
+    #[test]
+    fn test_traits() {
+        use crate::Betacode;
+        assert_eq!("".to_greek(Type::Default).unwrap(), "");
+        assert_eq!(" ab ".to_greek(Type::Default).unwrap(), "αβ");
+        assert_eq!(String::from("").to_greek(Type::Default).unwrap(), "");
+        assert_eq!(String::from("sa").to_greek(Type::Default).unwrap(), "σα");
+    }
+
+    #[test]
+    fn valid_default_encoding() {
+        assert_eq!(to_greek("", Type::Default).unwrap(), "");
+        assert_eq!(to_greek(" ", Type::Default).unwrap(), "");
+        assert_eq!(to_greek("  ", Type::Default).unwrap(), "");
+        assert_eq!(to_greek("a", Type::Default).unwrap(), "α");
+        assert_eq!(to_greek("a)", Type::Default).unwrap(), "ἀ");
+        assert_eq!(to_greek("s", Type::Default).unwrap(), "ς");
+        assert_eq!(to_greek("es", Type::Default).unwrap(), "ες");
+        assert_eq!(to_greek("es1", Type::Default).unwrap(), "εσ");
+        assert_eq!(to_greek("es2", Type::Default).unwrap(), "ες");
+        assert_eq!(to_greek("es3", Type::Default).unwrap(), "εϲ");
+        assert_eq!(to_greek("sos", Type::Default).unwrap(), "σος");
+        assert_eq!(to_greek("a)bba", Type::Default).unwrap(), "ἀββα");
+        assert_eq!(to_greek("a)p'", Type::Default).unwrap(), "ἀπ᾽");
+        assert_eq!(to_greek(" d' ", Type::Default).unwrap(), "δ᾽");
+        assert_eq!(to_greek(" a(ll", Type::Default).unwrap(), "ἁλλ");
+        assert_eq!(to_greek("kai\\ ", Type::Default).unwrap(), "καὶ");
+        assert_eq!(to_greek("cri", Type::Default).unwrap(), "χρι");
+        assert_eq!(to_greek("criv", Type::Default).unwrap(), "χρις");
+        assert_eq!(to_greek("criv kai\\", Type::Default).unwrap(), "χρις καὶ");
+        assert_eq!(to_greek("criv, kai\\", Type::Default).unwrap(), "χρις, καὶ");
+        assert_eq!(to_greek("criv. kai\\", Type::Default).unwrap(), "χρις. καὶ");
+        assert_eq!(to_greek("crivkai\\", Type::Default).unwrap(), "χριςκαὶ");
+        assert_eq!(to_greek("crij kai\\", Type::Default).unwrap(), "χρις καὶ");
+        assert_eq!(to_greek("crisv", Type::Default).unwrap(), "χρισς");
+        assert_eq!(to_greek("Qeo/v", Type::Default).unwrap(), "Θεός");
+        assert_eq!(to_greek("qeo/s3", Type::Default).unwrap(), "θεόϲ");
+        assert_eq!(to_greek("v1 v2 v3", Type::Default).unwrap(), "σ ς ϲ");
+        let lunate = Converter::new(Type::Default).with_sigma(SigmaPolicy::Lunate);
+        assert_eq!(lunate.to_greek("criv crivkai\\").unwrap(), "χριϲ χριϲκαὶ");
+        let medial = Converter::new(Type::Default).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(medial.to_greek("criv crivkai\\").unwrap(), "χρισ χρισκαὶ");
+        assert_eq!(medial.to_greek("criv2").unwrap(), "χρις");
+    }
+
+    #[test]
+    fn default_j_sigma() {
+        assert_eq!(to_greek("Qeo/j", Type::Default).unwrap(), "Θεός");
+        assert_eq!(to_greek("jw/zw", Type::Default).unwrap(), "σώζω");
+        assert_eq!(to_greek("a)jpa/zomai", Type::Default).unwrap(), "ἀσπάζομαι");
+        assert_eq!(to_greek("Jw/zw", Type::Default).unwrap(), "Σώζω");
+        assert_eq!(to_greek("j1 j2 j3", Type::Default).unwrap(), "σ ς ϲ");
+        assert_eq!(to_greek("crij-", Type::Default).unwrap(), "χρισ");
+        // j follows the position of the sigma, where v is always a final
+        // sigma, but both follow the lunate and medial policies.
+        assert_eq!(to_greek("vw/zw", Type::Default).unwrap(), "ςώζω");
+        let lunate = Converter::new(Type::Default).with_sigma(SigmaPolicy::Lunate);
+        assert_eq!(lunate.to_greek("jw/zw Qeo/j").unwrap(), "ϲώζω Θεόϲ");
+        assert_eq!(lunate.to_greek("vw/zw Qeo/v").unwrap(), "ϲώζω Θεόϲ");
+        assert_eq!(lunate.to_greek("J V").unwrap(), "Ϲ Ϲ");
+        let medial = Converter::new(Type::Default).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(medial.to_greek("jw/zw Qeo/j").unwrap(), "σώζω Θεόσ");
+        assert_eq!(medial.to_greek("vw/zw Qeo/v").unwrap(), "σώζω Θεόσ");
+        assert_eq!(greek_letter(b'j', Type::Default), Some('σ'));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", GreekDisplay("qeo/s", Type::TLG)), "θεός");
+        assert_eq!(format!("{}", GreekDisplay("Qeo/s", Type::Default)), "Θεός");
+        assert_eq!(
+            format!("[{}]", GreekDisplay("  kai\\ lo/gos  ", Type::TLG)),
+            "[καὶ λόγος]"
+        );
+        assert_eq!(
+            format!("{}", GreekDisplay("qeo/s! lo/gos", Type::TLG)),
+            "θεός\u{fffd} λόγος"
+        );
+        assert_eq!(format!("{}", GreekDisplay("", Type::TLG)), "");
+    }
+
+    #[test]
+    fn explicit_sigma_at_end() {
+        // An explicit sigma form is kept at the end of the string, whichever
+        // sigma policy is chosen.
+        for sigma in [SigmaPolicy::Auto, SigmaPolicy::Lunate, SigmaPolicy::Medial] {
+            for version in [Type::Default, Type::TLG] {
+                let options = Converter::new(version).with_sigma(sigma);
+                for (betacode, greek) in [
+                    ("s1", "σ"),
+                    ("s2", "ς"),
+                    ("s3", "ϲ"),
+                    ("es1", "εσ"),
+                    ("es2", "ες"),
+                    ("es3", "εϲ"),
+                    ("o(s3", "ὁϲ"),
+                    ("a)/s1", "ἄσ"),
+                    ("w(=|s2", "ᾧς"),
+                    ("qeo/s3", "θεόϲ"),
+                    ("qeo/s3 ", "θεόϲ"),
+                    ("qeo/s3.", "θεόϲ."),
+                ] {
+                    assert_eq!(options.to_greek(betacode).unwrap(), greek, "{}", betacode);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tau() {
+        assert_eq!(to_greek("te", Type::Default).unwrap(), "τε");
+        assert_eq!(to_greek("tau", Type::Default).unwrap(), "ταυ");
+        assert_eq!(to_greek("tauros", Type::Default).unwrap(), "ταυρος");
+        assert_eq!(to_greek("Tauros", Type::Default).unwrap(), "Ταυρος");
+        assert_eq!(to_greek("au)to/s", Type::Default).unwrap(), "αὐτός");
+        assert_eq!(to_greek("*tau", Type::TLG).unwrap(), "Ταυ");
+        assert_eq!(to_greek("au)to/s", Type::TLG).unwrap(), "αὐτός");
+    }
+
+    #[test]
+    fn omicron_breathing() {
+        assert_eq!(to_greek("o)", Type::Default).unwrap(), "ὀ");
+        assert_eq!(to_greek("o(", Type::Default).unwrap(), "ὁ");
+        assert_eq!(to_greek("o\\", Type::Default).unwrap(), "ὸ");
+        assert_eq!(to_greek("o)/", Type::Default).unwrap(), "ὄ");
+        assert_eq!(to_greek("o(/", Type::Default).unwrap(), "ὅ");
+        assert_eq!(to_greek("O)", Type::Default).unwrap(), "Ὀ");
+        assert_eq!(to_greek("O(", Type::Default).unwrap(), "Ὁ");
+        assert_eq!(to_greek("O\\", Type::Default).unwrap(), "Ὸ");
+        assert_eq!(to_greek("O)/", Type::Default).unwrap(), "Ὄ");
+        assert_eq!(to_greek("O(/", Type::Default).unwrap(), "Ὅ");
+        assert_eq!(to_greek("*o)", Type::TLG).unwrap(), "Ὀ");
+        assert_eq!(to_greek("*o(/", Type::TLG).unwrap(), "Ὅ");
+    }
+
+    #[test]
+    fn error_messages() {
+        let e = ConversionError::UnexpectedCharacter('#', 4);
+        assert_eq!(e.to_string(), "unexpected character '#' at byte 4");
+        let e = ConversionError::UnexpectedAccent('α', 2);
+        assert_eq!(e.to_string(), "invalid accent on 'α' at byte 2");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn std_error() {
+        fn convert() -> Result<String, std::boxed::Box<dyn std::error::Error>> {
+            Ok(to_greek("a/\\", Type::Default)?)
+        }
+        assert!(convert().is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn alloc_only() {
+        let word: alloc::string::String = to_greek("kai\\ qeo/s", Type::TLG).unwrap();
+        assert_eq!(word, "καὶ θεός");
+        let mut word = alloc::string::String::new();
+        to_greek_into("lo/gos", Type::TLG, &mut word).unwrap();
+        assert_eq!(word, "λόγος");
+        assert!(to_greek("qeo/s!", Type::TLG).is_err());
+    }
+
+    #[test]
+    fn multiple_words() {
+        assert_eq!(to_greek("kai\\ qeo/s", Type::TLG).unwrap(), "καὶ θεός");
+        assert_eq!(to_greek("kai\\ qeo/v", Type::Default).unwrap(), "καὶ θεός");
+        assert_eq!(to_greek("es es", Type::Default).unwrap(), "ες ες");
+        assert_eq!(to_greek("  es   es  ", Type::Default).unwrap(), "ες ες");
+        assert_eq!(to_greek("es\tes\nes", Type::Default).unwrap(), "ες ες ες");
+        assert_eq!(to_greek("sos sos", Type::TLG).unwrap(), "σος σος");
+        assert_eq!(to_greek("a)ll' ", Type::Default).unwrap(), "ἀλλ᾽");
+        assert!(to_greek("a/\\ es", Type::Default).is_err());
+        assert!(to_greek("es a/\\", Type::Default).is_err());
+    }
+
+    #[test]
+    fn separators() {
+        for (betacode, greek) in [
+            ("qeo/s  kai\\", "θεός καὶ"),
+            ("  qeo/s kai\\  ", "θεός καὶ"),
+            ("\tqeo/s  kai\\ lo/gos\t", "θεός καὶ λόγος"),
+            ("qeo/s\tkai\\", "θεός καὶ"),
+            ("qeo/s \t\r\n kai\\", "θεός καὶ"),
+            ("qeo/s , , kai\\", "θεός,, καὶ"),
+            ("qeo/s .  . kai\\", "θεός.. καὶ"),
+            (" , qeo/s", ", θεός"),
+            ("   ", ""),
+            ("\t\r\n", ""),
+        ] {
+            assert_eq!(
+                to_greek(betacode, Type::TLG).unwrap(),
+                greek,
+                "{:?}",
+                betacode
+            );
+            assert_eq!(validate(betacode, Type::TLG), Ok(()));
+            let chars: String = GreekChars::new(betacode.bytes(), Converter::new(Type::TLG))
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(chars, greek, "{:?}", betacode);
+        }
+    }
+
+    #[test]
+    fn final_sigma_before_punctuation() {
+        assert_eq!(to_greek("lo/gos,", Type::TLG).unwrap(), "λόγος,");
+        assert_eq!(to_greek("lo/gos.", Type::TLG).unwrap(), "λόγος.");
+        assert_eq!(to_greek("lo/gos:", Type::TLG).unwrap(), "λόγος\u{387}");
+        assert_eq!(
+            to_greek("lo/gov, kai\\", Type::Default).unwrap(),
+            "λόγος, καὶ"
+        );
+        assert_eq!(to_greek("qeo/s. o(", Type::TLG).unwrap(), "θεός. ὁ");
+        assert_eq!(to_greek("sos,sos", Type::TLG).unwrap(), "σος,σος");
+        assert_eq!(to_greek("qeo/s1,", Type::TLG).unwrap(), "θεόσ,");
+    }
+
+    #[test]
+    fn capital_pi() {
+        assert_eq!(to_greek("PATHR", Type::Default).unwrap(), "ΠΑΤΗΡ");
+        assert_eq!(to_greek("Pe/tros", Type::Default).unwrap(), "Πέτρος");
+        assert_eq!(to_greek("*PATHR", Type::TLG).unwrap(), "Πατηρ");
+        assert_eq!(to_greek("*pe/tros", Type::TLG).unwrap(), "Πέτρος");
+        assert_eq!(to_greek("PATHR", Type::TLG).unwrap(), "πατηρ");
+    }
+
+    #[test]
+    fn char_index() {
+        let input = "lo/gos!";
+        let e = to_greek(input, Type::TLG).unwrap_err();
+        assert_eq!(e.byte_index(), 6);
+        assert_eq!(e.char_index(input), 6);
+        let input = "λόγβ\u{301}";
+        let e = to_betacode(input, Type::TLG).unwrap_err();
+        assert_eq!(e, ConversionError::UnexpectedAccent('β', 6));
+        assert_eq!(e.char_index(input), 3);
+        let input = "λόγος ἀμήν";
+        let e = to_greek(input, Type::TLG).unwrap_err();
+        assert_eq!(e.char_index(input), 0);
+        assert_eq!(e.char_index(""), 0);
+    }
+
+    #[test]
+    fn nul() {
+        assert_eq!(
+            to_greek("lo/gos\0kai\\", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\0', 6)
+        );
+        assert_eq!(
+            to_greek("\0", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\0', 0)
+        );
+        assert_eq!(to_greek_lossy("lo/gos\0", Type::TLG), "λόγος\u{fffd}");
+        assert_eq!(
+            to_greek(" \t lo/gos \r\n kai\\ \n", Type::TLG).unwrap(),
+            "λόγος καὶ"
+        );
+    }
+
+    #[test]
+    fn unexpected_character_position() {
+        assert_eq!(
+            to_greek("qeo/s!", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 5)
+        );
+        assert_eq!(
+            to_greek("a)p'b", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedCharacter('b', 4)
+        );
+        assert_eq!(
+            to_greek("a/\\", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedAccent('α', 0)
+        );
+        // An accent with no letter before it is reported where it is found.
+        assert_eq!(
+            to_greek(")a", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter(')', 0)
+        );
+        assert_eq!(
+            to_greek("ab )d", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter(')', 3)
+        );
+        assert_eq!(
+            to_greek("abc, /d", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedCharacter('/', 5)
+        );
+        assert_eq!(
+            to_greek_all_errors("a ) b (", Type::TLG).1,
+            vec![
+                ConversionError::UnexpectedCharacter(')', 2),
+                ConversionError::UnexpectedCharacter('(', 6),
+            ]
+        );
+
+        let errors: std::collections::HashSet<ConversionError> = ["a!", "a!", "b!"]
+            .iter()
+            .filter_map(|s| to_greek(s, Type::Default).err())
+            .collect();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn capital_prosgegrammeni() {
+        let accents = ["", ")", "(", ")/", "(/", ")\\", "(\\", ")=", "(="];
+        for (letter, expected) in [('a', "ᾼᾈᾉᾌᾍᾊᾋᾎᾏ"), ('h', "ῌᾘᾙᾜᾝᾚᾛᾞᾟ"), ('w', "ῼᾨᾩᾬᾭᾪᾫᾮᾯ")]
+        {
+            for (accent, e) in accents.iter().zip(expected.chars()) {
+                let tlg = format!("*{}{}|", accent, letter);
+                assert_eq!(to_greek(&tlg, Type::TLG).unwrap(), e.to_string(), "{}", tlg);
+                let tlg = format!("*{}{}|", letter, accent);
+                assert_eq!(to_greek(&tlg, Type::TLG).unwrap(), e.to_string(), "{}", tlg);
+                let default = format!("{}{}|", letter.to_ascii_uppercase(), accent);
+                assert_eq!(
+                    to_greek(&default, Type::Default).unwrap(),
+                    e.to_string(),
+                    "{}",
+                    default
+                );
+            }
+        }
+        assert_eq!(
+            to_greek("*(=|w au)tou= h( do/ca.", Type::TLG).unwrap(),
+            "ᾯ αὐτοῦ ἡ δόξα."
+        );
+        assert_eq!(to_greek("*)=|a kai\\ *(/|h", Type::TLG).unwrap(), "ᾎ καὶ ᾝ");
+        assert_eq!(to_greek("*(/A|IDHI", Type::TLG).unwrap(), "ᾍιδηι");
+        assert_eq!(to_greek("*a/|", Type::TLG).unwrap(), "Α\u{301}\u{345}");
+        assert_eq!(to_greek("*W=|", Type::TLG).unwrap(), "Ω\u{342}\u{345}");
+        assert_eq!(to_greek("H\\|", Type::Default).unwrap(), "Η\u{300}\u{345}");
+    }
+
+    #[test]
+    fn iota_subscript() {
+        assert_eq!(to_greek("a)|", Type::Default).unwrap(), "ᾀ");
+        assert_eq!(to_greek("a/|", Type::Default).unwrap(), "ᾴ");
+        assert_eq!(to_greek("a=|", Type::Default).unwrap(), "ᾷ");
+        assert_eq!(to_greek("h/|", Type::Default).unwrap(), "ῄ");
+        assert_eq!(to_greek("w(=|", Type::Default).unwrap(), "ᾧ");
+        assert_eq!(to_greek("h)\\|", Type::Default).unwrap(), "ᾒ");
+        assert_eq!(to_greek("*a)|", Type::TLG).unwrap(), "ᾈ");
+        assert_eq!(to_greek("*w|", Type::TLG).unwrap(), "ῼ");
+        assert_eq!(
+            to_greek("*do/ca e)n u(yi/stois qew=|", Type::TLG).unwrap(),
+            "Δόξα ἐν ὑψίστοις θεῷ"
+        );
+        assert_eq!(
+            to_greek(
+                "tw=| patri\\ kai\\ tw=| ui(w=| kai\\ tw=| a(gi/w| pneu/mati",
+                Type::TLG
+            )
+            .unwrap(),
+            "τῷ πατρὶ καὶ τῷ υἱῷ καὶ τῷ ἁγίῳ πνεύματι"
+        );
+        assert_eq!(
+            to_greek("e)n th=| h(me/ra| e)kei/nh|", Type::TLG).unwrap(),
+            "ἐν τῇ ἡμέρᾳ ἐκείνῃ"
+        );
+    }
+
+    #[test]
+    fn rho_breathing() {
+        assert_eq!(to_greek("r)", Type::Default).unwrap(), "ῤ");
+        assert_eq!(to_greek("r(", Type::Default).unwrap(), "ῥ");
+        assert_eq!(to_greek("pu/r)r(os", Type::TLG).unwrap(), "πύῤῥος");
+        assert_eq!(to_greek("a)r)r(abw/n", Type::TLG).unwrap(), "ἀῤῥαβών");
+    }
+
+    #[test]
+    fn cow() {
+        assert!(matches!(to_greek_cow("", Type::TLG), Ok(Cow::Borrowed(""))));
+        assert!(matches!(
+            to_greek_cow(" \t\n ", Type::TLG),
+            Ok(Cow::Borrowed(""))
+        ));
+        assert!(matches!(
+            to_greek_cow("qeo/s", Type::TLG),
+            Ok(Cow::Owned(_))
+        ));
+        assert_eq!(to_greek_cow("qeo/s", Type::TLG).unwrap(), "θεός");
+        assert!(to_greek_cow("qeo/s!", Type::TLG).is_err());
+    }
+
+    #[test]
+    fn into() {
+        let mut word = String::new();
+        to_greek_into("qeo/s", Type::TLG, &mut word).unwrap();
+        assert_eq!(word, "θεός");
+        let capacity = word.capacity();
+        to_greek_into("kai\\", Type::TLG, &mut word).unwrap();
+        assert_eq!(word, "καὶ");
+        assert_eq!(word.capacity(), capacity);
+        to_greek_into("", Type::TLG, &mut word).unwrap();
+        assert_eq!(word, "");
+        assert!(to_greek_into("qeo/s!", Type::TLG, &mut word).is_err());
+    }
+
+    #[test]
+    fn bytes_into() {
+        let mut word = String::new();
+        for version in [Type::Default, Type::TLG] {
+            for input in [
+                "qeo/s",
+                "kai\\ lo/gos",
+                "",
+                "  ",
+                "a)ll'",
+                "*)/a%27|",
+                "qeo/s!",
+            ] {
+                let result = to_greek_bytes_into(input.as_bytes(), version, &mut word);
+                match to_greek(input, version) {
+                    Ok(greek) => {
+                        assert_eq!(result, Ok(()));
+                        assert_eq!(word, greek);
+                    }
+                    Err(e) => assert_eq!(result, Err(e)),
+                }
+            }
+        }
+        to_greek_bytes_into(b"lo/gos", Type::TLG, &mut word).unwrap();
+        let capacity = word.capacity();
+        to_greek_bytes_into(b"qeo/s", Type::TLG, &mut word).unwrap();
+        assert_eq!(word, "θεός");
+        assert_eq!(word.capacity(), capacity);
+        assert_eq!(
+            to_greek_bytes_into(b"qe\xcf\x89", Type::TLG, &mut word),
+            Err(ConversionError::UnexpectedCharacter('\u{cf}', 2))
+        );
+        assert_eq!(word, "θε");
+    }
+
+    #[test]
+    fn default_type() {
+        assert_eq!(Type::default(), Type::Default);
+        assert_eq!(to_greek("Qeo/v", Type::default()).unwrap(), "Θεός");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_type() {
+        for version in [Type::Default, Type::TLG, Type::Perseus] {
+            let json = serde_json::to_string(&version).unwrap();
+            assert_eq!(serde_json::from_str::<Type>(&json).unwrap(), version);
+        }
+        assert_eq!(
+            serde_json::to_string(&Type::Default).unwrap(),
+            "\"default\""
+        );
+        assert_eq!(serde_json::to_string(&Type::TLG).unwrap(), "\"tlg\"");
+        assert_eq!(serde_json::from_str::<Type>("\"TLG\"").unwrap(), Type::TLG);
+        let e = serde_json::from_str::<Type>("\"latin\"").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "unknown variant `latin`, expected one of `default`, `tlg`, `perseus` at line 1 column 7"
+        );
+        assert!(serde_json::from_str::<Type>("1").is_err());
+    }
+
+    #[test]
+    fn parse_type() {
+        assert_eq!("default".parse::<Type>(), Ok(Type::Default));
+        assert_eq!("DEFAULT".parse::<Type>(), Ok(Type::Default));
+        assert_eq!("standard".parse::<Type>(), Ok(Type::Default));
+        assert_eq!("Beta".parse::<Type>(), Ok(Type::Default));
+        assert_eq!("tlg".parse::<Type>(), Ok(Type::TLG));
+        assert_eq!("TLG".parse::<Type>(), Ok(Type::TLG));
+        assert_eq!("Perseus".parse::<Type>(), Ok(Type::Perseus));
+        let e = "latin".parse::<Type>().unwrap_err();
+        assert_eq!(e, ParseTypeError("latin".to_string()));
+        assert_eq!(
+            e.to_string(),
+            "unknown betacode type 'latin', expected 'default', 'tlg' or 'perseus'"
+        );
+        assert!("".parse::<Type>().is_err());
+        assert_eq!(Type::try_from("tlg"), Ok(Type::TLG));
+        assert_eq!(Type::try_from("Default"), Ok(Type::Default));
+        assert_eq!(
+            Type::try_from("latin").unwrap_err().to_string(),
+            "unknown betacode type 'latin', expected 'default', 'tlg' or 'perseus'"
+        );
+    }
+
+    #[test]
+    fn nfd() {
+        assert_eq!(to_greek_nfd("e)/", Type::TLG).unwrap(), "ε\u{313}\u{301}");
+        assert_eq!(to_greek_nfd("qeo/s", Type::TLG).unwrap(), "θεο\u{301}ς");
+        assert_eq!(
+            to_greek_nfd("w(=|", Type::TLG).unwrap(),
+            "ω\u{314}\u{342}\u{345}"
+        );
+        assert_eq!(
+            to_greek_nfd("lo/gos:", Type::TLG).unwrap(),
+            "λο\u{301}γος\u{b7}"
+        );
+        assert_eq!(to_greek_nfd("", Type::TLG).unwrap(), "");
+    }
+
+    #[test]
+    fn nfd_matches_unicode() {
+        use unicode_normalization::UnicodeNormalization;
+        let ranges = [0x340..0x345, 0x370..0x400, 0x1f00..0x2000];
+        for c in ranges.into_iter().flatten().filter_map(char::from_u32) {
+            let expected: String = c.nfd().collect();
+            let actual = decompose(c).map(str::to_string).unwrap_or(c.to_string());
+            assert_eq!(actual, expected, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(validate("", Type::TLG), Ok(()));
+        assert_eq!(validate("kai\\ qeo/s", Type::TLG), Ok(()));
+        assert_eq!(validate("a)p' ", Type::Default), Ok(()));
+        for input in ["qeo/s!", "a/\\", "*a", "dε", " a)p'b", "a ) b"] {
+            assert_eq!(
+                validate(input, Type::Default),
+                to_greek(input, Type::Default).map(|_| ())
+            );
+        }
+    }
+
+    #[test]
+    fn capital_upsilon_smooth() {
+        assert_eq!(to_greek("U)", Type::Default).unwrap(), "\u{3a5}\u{313}");
+        assert_eq!(to_greek("*u)", Type::TLG).unwrap(), "\u{3a5}\u{313}");
+        assert_eq!(to_greek("*)u", Type::TLG).unwrap(), "\u{3a5}\u{313}");
+        assert_eq!(
+            to_greek("*u)=", Type::TLG).unwrap(),
+            "\u{3a5}\u{313}\u{342}"
+        );
+        assert_eq!(
+            to_greek("*u)/", Type::TLG).unwrap(),
+            "\u{3a5}\u{313}\u{301}"
+        );
+        assert_eq!(
+            to_greek("*u)\\", Type::TLG).unwrap(),
+            "\u{3a5}\u{313}\u{300}"
+        );
+        assert_eq!(to_greek("u)", Type::Default).unwrap(), "ὐ");
+        assert_eq!(
+            to_greek("*u)=", Type::TLG).unwrap(),
+            to_greek_nfd("*u)=", Type::TLG).unwrap()
+        );
+    }
+
+    // A capital upsilon has a precomposed form with a rough breathing, but
+    // not with a smooth breathing. Both must agree with the decomposed form.
+    #[test]
+    fn capital_upsilon_rough() {
+        use unicode_normalization::UnicodeNormalization;
+        assert_eq!(to_greek("*u(", Type::TLG).unwrap(), "\u{1f59}");
+        assert_eq!(to_greek("*(u", Type::TLG).unwrap(), "Ὑ");
+        assert_eq!(to_greek("U(", Type::Default).unwrap(), "Ὑ");
+        assert_eq!(to_greek("u(", Type::TLG).unwrap(), "\u{1f51}");
+        assert_eq!(to_greek("*u(io/s", Type::TLG).unwrap(), "Ὑιός");
+        assert_eq!(to_greek("*(uio/s", Type::TLG).unwrap(), "Ὑιός");
+        assert_eq!(to_greek("*(/u", Type::TLG).unwrap(), "Ὕ");
+        assert_eq!(to_greek("*(=u", Type::TLG).unwrap(), "Ὗ");
+        assert_eq!(to_greek("*(\\u", Type::TLG).unwrap(), "Ὓ");
+        for input in [
+            "*u(", "*u(/", "*u(=", "*u(\\", "*u)", "*u)/", "*u)=", "*u)\\",
+        ] {
+            let greek = to_greek(input, Type::TLG).unwrap();
+            let expected: String = greek.nfd().collect();
+            assert_eq!(
+                to_greek_nfd(input, Type::TLG).unwrap(),
+                expected,
+                "{}",
+                input
+            );
+            assert!(greek.starts_with(|c: char| c.is_uppercase()), "{}", input);
+            let mut back = to_betacode(&greek, Type::TLG).unwrap();
+            assert_eq!(to_greek(&back, Type::TLG).unwrap(), greek);
+            back = to_betacode(&expected, Type::TLG).unwrap();
+            assert_eq!(to_greek(&back, Type::TLG).unwrap(), greek);
+        }
+        assert_eq!(to_betacode("Ὑιός", Type::TLG).unwrap(), "*(uio/s");
+        assert_eq!(to_betacode("Υ\u{314}ιός", Type::TLG).unwrap(), "*(uio/s");
+        assert_eq!(to_betacode("Ὑιός", Type::Default).unwrap(), "U(io/s");
+    }
+
+    #[test]
+    fn epsilon_breathing_grave() {
+        assert_eq!(to_greek("e)\\", Type::Default).unwrap(), "ἒ");
+        assert_eq!(to_greek("e)/", Type::Default).unwrap(), "ἔ");
+        assert_eq!(to_greek("e(\\", Type::Default).unwrap(), "ἓ");
+        assert_eq!(to_greek("e(/", Type::Default).unwrap(), "ἕ");
+        assert_eq!(to_greek("*e)\\", Type::TLG).unwrap(), "Ἒ");
+        assert_eq!(to_greek("*e)/", Type::TLG).unwrap(), "Ἔ");
+        assert_eq!(to_greek("*e(\\", Type::TLG).unwrap(), "Ἓ");
+        assert_eq!(to_greek("*e(/", Type::TLG).unwrap(), "Ἕ");
+    }
+
+    #[test]
+    fn capital_diaeresis_accent() {
+        assert_eq!(
+            to_greek("*I+/", Type::TLG).unwrap(),
+            "\u{399}\u{308}\u{301}"
+        );
+        assert_eq!(
+            to_greek("*U+\\", Type::TLG).unwrap(),
+            "\u{3a5}\u{308}\u{300}"
+        );
+        assert_eq!(
+            to_greek("*I+\\", Type::TLG).unwrap(),
+            "\u{399}\u{308}\u{300}"
+        );
+        assert_eq!(
+            to_greek("U+/", Type::Default).unwrap(),
+            "\u{3a5}\u{308}\u{301}"
+        );
+        assert_eq!(to_greek("*I+", Type::TLG).unwrap(), "Ϊ");
+        assert_eq!(to_greek("i+/", Type::TLG).unwrap(), "\u{390}");
+    }
+
+    #[test]
+    fn omega_breathing_grave() {
+        assert_eq!(to_greek("*w)\\", Type::TLG).unwrap(), "Ὢ");
+        assert_eq!(to_greek("*w)/", Type::TLG).unwrap(), "Ὤ");
+        assert_eq!(to_greek("w)\\", Type::TLG).unwrap(), "ὢ");
+        assert_eq!(to_greek("*w(\\", Type::TLG).unwrap(), "Ὣ");
+    }
+
+    // The match statement used to look up accents before the ACCENTS table.
+    fn match_accent(c: char, accents: u16) -> char {
+        if accents == 0 {
+            return c;
+        }
+
+        match (c, accents) {
+            ('α', ASCII_SMOOTH) => 'ἀ',
+            ('ε', ASCII_SMOOTH) => 'ἐ',
+            ('ι', ASCII_SMOOTH) => 'ἰ',
+            ('η', ASCII_SMOOTH) => 'ἠ',
+            ('ο', ASCII_SMOOTH) => 'ὀ',
+            ('ω', ASCII_SMOOTH) => 'ὠ',
+            ('υ', ASCII_SMOOTH) => 'ὐ',
+            ('ρ', ASCII_SMOOTH) => 'ῤ',
+            ('Α', ASCII_SMOOTH) => 'Ἀ',
+            ('Ε', ASCII_SMOOTH) => 'Ἐ',
+            ('Ι', ASCII_SMOOTH) => 'Ἰ',
+            ('Η', ASCII_SMOOTH) => 'Ἠ',
+            ('Ο', ASCII_SMOOTH) => 'Ὀ',
+            ('Ω', ASCII_SMOOTH) => 'Ὠ',
+            ('α', ASCII_ROUGH) => 'ἁ',
+            ('ε', ASCII_ROUGH) => 'ἑ',
+            ('ι', ASCII_ROUGH) => 'ἱ',
+            ('η', ASCII_ROUGH) => 'ἡ',
+            ('ο', ASCII_ROUGH) => 'ὁ',
+            ('ω', ASCII_ROUGH) => 'ὡ',
+            ('υ', ASCII_ROUGH) => 'ὑ',
+            ('ρ', ASCII_ROUGH) => 'ῥ',
+            ('Α', ASCII_ROUGH) => 'Ἁ',
+            ('Ε', ASCII_ROUGH) => 'Ἑ',
+            ('Ι', ASCII_ROUGH) => 'Ἱ',
+            ('Η', ASCII_ROUGH) => 'Ἡ',
+            ('Ο', ASCII_ROUGH) => 'Ὁ',
+            ('Ω', ASCII_ROUGH) => 'Ὡ',
+            ('Υ', ASCII_ROUGH) => 'Ὑ',
+            ('Ρ', ASCII_ROUGH) => 'Ῥ',
+            ('α', ASCII_ACUTE) => 'ά',
+            ('ε', ASCII_ACUTE) => 'έ',
+            ('ι', ASCII_ACUTE) => 'ί',
+            ('η', ASCII_ACUTE) => 'ή',
+            ('ο', ASCII_ACUTE) => 'ό',
+            ('ω', ASCII_ACUTE) => 'ώ',
+            ('υ', ASCII_ACUTE) => 'ύ',
+            ('Α', ASCII_ACUTE) => 'Ά',
+            ('Ε', ASCII_ACUTE) => 'Έ',
+            ('Ι', ASCII_ACUTE) => 'Ί',
+            ('Η', ASCII_ACUTE) => 'Ή',
+            ('Ο', ASCII_ACUTE) => 'Ό',
+            ('Ω', ASCII_ACUTE) => 'Ώ',
+            ('Υ', ASCII_ACUTE) => 'Ύ',
+            ('α', ASCII_GRAVE) => 'ὰ',
+            ('ε', ASCII_GRAVE) => 'ὲ',
+            ('ι', ASCII_GRAVE) => 'ὶ',
+            ('η', ASCII_GRAVE) => 'ὴ',
+            ('ο', ASCII_GRAVE) => 'ὸ',
+            ('ω', ASCII_GRAVE) => 'ὼ',
+            ('υ', ASCII_GRAVE) => 'ὺ',
+            ('Α', ASCII_GRAVE) => 'Ὰ',
+            ('Ε', ASCII_GRAVE) => 'Ὲ',
+            ('Ι', ASCII_GRAVE) => 'Ὶ',
+            ('Η', ASCII_GRAVE) => 'Ὴ',
+            ('Ο', ASCII_GRAVE) => 'Ὸ',
+            ('Ω', ASCII_GRAVE) => 'Ὼ',
+            ('Υ', ASCII_GRAVE) => 'Ὺ',
+            ('α', ASCII_CIRCUMFLEX) => 'ᾶ',
+            ('ι', ASCII_CIRCUMFLEX) => 'ῖ',
+            ('η', ASCII_CIRCUMFLEX) => 'ῆ',
+            ('ω', ASCII_CIRCUMFLEX) => 'ῶ',
+            ('υ', ASCII_CIRCUMFLEX) => 'ῦ',
+            ('α', ASCII_IOTA) => 'ᾳ',
+            ('η', ASCII_IOTA) => 'ῃ',
+            ('ω', ASCII_IOTA) => 'ῳ',
+            ('Α', ASCII_IOTA) => 'ᾼ',
+            ('Η', ASCII_IOTA) => 'ῌ',
+            ('Ω', ASCII_IOTA) => 'ῼ',
+            ('α', ASCII_IOTA_SMOOTH) => 'ᾀ',
+            ('η', ASCII_IOTA_SMOOTH) => 'ᾐ',
+            ('ω', ASCII_IOTA_SMOOTH) => 'ᾠ',
+            ('Α', ASCII_IOTA_SMOOTH) => 'ᾈ',
+            ('Η', ASCII_IOTA_SMOOTH) => 'ᾘ',
+            ('Ω', ASCII_IOTA_SMOOTH) => 'ᾨ',
+            ('α', ASCII_IOTA_ROUGH) => 'ᾁ',
+            ('η', ASCII_IOTA_ROUGH) => 'ᾑ',
+            ('ω', ASCII_IOTA_ROUGH) => 'ᾡ',
+            ('Α', ASCII_IOTA_ROUGH) => 'ᾉ',
+            ('Η', ASCII_IOTA_ROUGH) => 'ᾙ',
+            ('Ω', ASCII_IOTA_ROUGH) => 'ᾩ',
+            ('α', ASCII_IOTA_ACUTE) => 'ᾴ',
+            ('η', ASCII_IOTA_ACUTE) => 'ῄ',
+            ('ω', ASCII_IOTA_ACUTE) => 'ῴ',
+            ('α', ASCII_IOTA_GRAVE) => 'ᾲ',
+            ('η', ASCII_IOTA_GRAVE) => 'ῂ',
+            ('ω', ASCII_IOTA_GRAVE) => 'ῲ',
+            ('α', ASCII_IOTA_CIRCUMFLEX) => 'ᾷ',
+            ('η', ASCII_IOTA_CIRCUMFLEX) => 'ῇ',
+            ('ω', ASCII_IOTA_CIRCUMFLEX) => 'ῷ',
+            ('α', ASCII_IOTA_SMOOTH_ACUTE) => 'ᾄ',
+            ('η', ASCII_IOTA_SMOOTH_ACUTE) => 'ᾔ',
+            ('ω', ASCII_IOTA_SMOOTH_ACUTE) => 'ᾤ',
+            ('Α', ASCII_IOTA_SMOOTH_ACUTE) => 'ᾌ',
+            ('Η', ASCII_IOTA_SMOOTH_ACUTE) => 'ᾜ',
+            ('Ω', ASCII_IOTA_SMOOTH_ACUTE) => 'ᾬ',
+            ('α', ASCII_IOTA_SMOOTH_GRAVE) => 'ᾂ',
+            ('η', ASCII_IOTA_SMOOTH_GRAVE) => 'ᾒ',
+            ('ω', ASCII_IOTA_SMOOTH_GRAVE) => 'ᾢ',
+            ('Α', ASCII_IOTA_SMOOTH_GRAVE) => 'ᾊ',
+            ('Η', ASCII_IOTA_SMOOTH_GRAVE) => 'ᾚ',
+            ('Ω', ASCII_IOTA_SMOOTH_GRAVE) => 'ᾪ',
+            ('α', ASCII_IOTA_ROUGH_ACUTE) => 'ᾅ',
+            ('η', ASCII_IOTA_ROUGH_ACUTE) => 'ᾕ',
+            ('ω', ASCII_IOTA_ROUGH_ACUTE) => 'ᾥ',
+            ('Α', ASCII_IOTA_ROUGH_ACUTE) => 'ᾍ',
+            ('Η', ASCII_IOTA_ROUGH_ACUTE) => 'ᾝ',
+            ('Ω', ASCII_IOTA_ROUGH_ACUTE) => 'ᾭ',
+            ('α', ASCII_IOTA_ROUGH_GRAVE) => 'ᾃ',
+            ('η', ASCII_IOTA_ROUGH_GRAVE) => 'ᾓ',
+            ('ω', ASCII_IOTA_ROUGH_GRAVE) => 'ᾣ',
+            ('Α', ASCII_IOTA_ROUGH_GRAVE) => 'ᾋ',
+            ('Η', ASCII_IOTA_ROUGH_GRAVE) => 'ᾛ',
+            ('Ω', ASCII_IOTA_ROUGH_GRAVE) => 'ᾫ',
+            ('α', ASCII_IOTA_CIRCUMFLEX_SMOOTH) => 'ᾆ',
+            ('η', ASCII_IOTA_CIRCUMFLEX_SMOOTH) => 'ᾖ',
+            ('ω', ASCII_IOTA_CIRCUMFLEX_SMOOTH) => 'ᾦ',
+            ('Α', ASCII_IOTA_CIRCUMFLEX_SMOOTH) => 'ᾎ',
+            ('Η', ASCII_IOTA_CIRCUMFLEX_SMOOTH) => 'ᾞ',
+            ('Ω', ASCII_IOTA_CIRCUMFLEX_SMOOTH) => 'ᾮ',
+            ('α', ASCII_IOTA_CIRCUMFLEX_ROUGH) => 'ᾇ',
+            ('η', ASCII_IOTA_CIRCUMFLEX_ROUGH) => 'ᾗ',
+            ('ω', ASCII_IOTA_CIRCUMFLEX_ROUGH) => 'ᾧ',
+            ('Α', ASCII_IOTA_CIRCUMFLEX_ROUGH) => 'ᾏ',
+            ('Η', ASCII_IOTA_CIRCUMFLEX_ROUGH) => 'ᾟ',
+            ('Ω', ASCII_IOTA_CIRCUMFLEX_ROUGH) => 'ᾯ',
+            ('α', ASCII_SMOOTH_GRAVE) => 'ἂ',
+            ('ε', ASCII_SMOOTH_GRAVE) => 'ἒ',
+            ('ι', ASCII_SMOOTH_GRAVE) => 'ἲ',
+            ('η', ASCII_SMOOTH_GRAVE) => 'ἢ',
+            ('ο', ASCII_SMOOTH_GRAVE) => 'ὂ',
+            ('ω', ASCII_SMOOTH_GRAVE) => 'ὢ',
+            ('υ', ASCII_SMOOTH_GRAVE) => 'ὒ',
+            ('Α', ASCII_SMOOTH_GRAVE) => 'Ἂ',
+            ('Ε', ASCII_SMOOTH_GRAVE) => 'Ἒ',
+            ('Ι', ASCII_SMOOTH_GRAVE) => 'Ἲ',
+            ('Η', ASCII_SMOOTH_GRAVE) => 'Ἢ',
+            ('Ο', ASCII_SMOOTH_GRAVE) => 'Ὂ',
+            ('Ω', ASCII_SMOOTH_GRAVE) => 'Ὢ',
+            ('α', ASCII_ROUGH_GRAVE) => 'ἃ',
+            ('ε', ASCII_ROUGH_GRAVE) => 'ἓ',
+            ('ι', ASCII_ROUGH_GRAVE) => 'ἳ',
+            ('η', ASCII_ROUGH_GRAVE) => 'ἣ',
+            ('ο', ASCII_ROUGH_GRAVE) => 'ὃ',
+            ('ω', ASCII_ROUGH_GRAVE) => 'ὣ',
+            ('υ', ASCII_ROUGH_GRAVE) => 'ὓ',
+            ('Α', ASCII_ROUGH_GRAVE) => 'Ἃ',
+            ('Ε', ASCII_ROUGH_GRAVE) => 'Ἓ',
+            ('Ι', ASCII_ROUGH_GRAVE) => 'Ἳ',
+            ('Η', ASCII_ROUGH_GRAVE) => 'Ἣ',
+            ('Ο', ASCII_ROUGH_GRAVE) => 'Ὃ',
+            ('Ω', ASCII_ROUGH_GRAVE) => 'Ὣ',
+            ('Υ', ASCII_ROUGH_GRAVE) => 'Ὓ',
+            ('α', ASCII_SMOOTH_ACUTE) => 'ἄ',
+            ('ε', ASCII_SMOOTH_ACUTE) => 'ἔ',
+            ('ι', ASCII_SMOOTH_ACUTE) => 'ἴ',
+            ('η', ASCII_SMOOTH_ACUTE) => 'ἤ',
+            ('ο', ASCII_SMOOTH_ACUTE) => 'ὄ',
+            ('ω', ASCII_SMOOTH_ACUTE) => 'ὤ',
+            ('υ', ASCII_SMOOTH_ACUTE) => 'ὔ',
+            ('Α', ASCII_SMOOTH_ACUTE) => 'Ἄ',
+            ('Ε', ASCII_SMOOTH_ACUTE) => 'Ἔ',
+            ('Ι', ASCII_SMOOTH_ACUTE) => 'Ἴ',
+            ('Η', ASCII_SMOOTH_ACUTE) => 'Ἤ',
+            ('Ο', ASCII_SMOOTH_ACUTE) => 'Ὄ',
+            ('Ω', ASCII_SMOOTH_ACUTE) => 'Ὤ',
+            ('α', ASCII_ROUGH_ACUTE) => 'ἅ',
+            ('ε', ASCII_ROUGH_ACUTE) => 'ἕ',
+            ('ι', ASCII_ROUGH_ACUTE) => 'ἵ',
+            ('η', ASCII_ROUGH_ACUTE) => 'ἥ',
+            ('ο', ASCII_ROUGH_ACUTE) => 'ὅ',
+            ('ω', ASCII_ROUGH_ACUTE) => 'ὥ',
+            ('υ', ASCII_ROUGH_ACUTE) => 'ὕ',
+            ('Α', ASCII_ROUGH_ACUTE) => 'Ἅ',
+            ('Ε', ASCII_ROUGH_ACUTE) => 'Ἕ',
+            ('Ι', ASCII_ROUGH_ACUTE) => 'Ἵ',
+            ('Η', ASCII_ROUGH_ACUTE) => 'Ἥ',
+            ('Ο', ASCII_ROUGH_ACUTE) => 'Ὅ',
+            ('Ω', ASCII_ROUGH_ACUTE) => 'Ὥ',
+            ('Υ', ASCII_ROUGH_ACUTE) => 'Ὕ',
+            ('ι', ASCII_DIAERESIS) => 'ϊ',
+            ('υ', ASCII_DIAERESIS) => 'ϋ',
+            ('Ι', ASCII_DIAERESIS) => 'Ϊ',
+            ('Υ', ASCII_DIAERESIS) => 'Ϋ',
+            ('ι', ASCII_DIAERESIS_GRAVE) => 'ῒ',
+            ('υ', ASCII_DIAERESIS_GRAVE) => 'ῢ',
+            ('ι', ASCII_DIAERESIS_ACUTE) => 'ΐ',
+            ('υ', ASCII_DIAERESIS_ACUTE) => 'ΰ',
+            ('ι', ASCII_DIAERESIS_CIRCUMFLEX) => 'ῗ',
+            ('υ', ASCII_DIAERESIS_CIRCUMFLEX) => 'ῧ',
+            ('α', ASCII_CIRCUMFLEX_SMOOTH) => 'ἆ',
+            ('η', ASCII_CIRCUMFLEX_SMOOTH) => 'ἦ',
+            ('ι', ASCII_CIRCUMFLEX_SMOOTH) => 'ἶ',
+            ('ω', ASCII_CIRCUMFLEX_SMOOTH) => 'ὦ',
+            ('υ', ASCII_CIRCUMFLEX_SMOOTH) => 'ὖ',
+            ('Α', ASCII_CIRCUMFLEX_SMOOTH) => 'Ἆ',
+            ('Η', ASCII_CIRCUMFLEX_SMOOTH) => 'Ἦ',
+            ('Ι', ASCII_CIRCUMFLEX_SMOOTH) => 'Ἶ',
+            ('Ω', ASCII_CIRCUMFLEX_SMOOTH) => 'Ὦ',
+            ('α', ASCII_CIRCUMFLEX_ROUGH) => 'ἇ',
+            ('η', ASCII_CIRCUMFLEX_ROUGH) => 'ἧ',
+            ('ι', ASCII_CIRCUMFLEX_ROUGH) => 'ἷ',
+            ('ω', ASCII_CIRCUMFLEX_ROUGH) => 'ὧ',
+            ('υ', ASCII_CIRCUMFLEX_ROUGH) => 'ὗ',
+            ('Α', ASCII_CIRCUMFLEX_ROUGH) => 'Ἇ',
+            ('Η', ASCII_CIRCUMFLEX_ROUGH) => 'Ἧ',
+            ('Ι', ASCII_CIRCUMFLEX_ROUGH) => 'Ἷ',
+            ('Ω', ASCII_CIRCUMFLEX_ROUGH) => 'Ὧ',
+            ('Υ', ASCII_CIRCUMFLEX_ROUGH) => 'Ὗ',
+            ('σ', ASCII_SIGMA1) => 'σ',
+            ('σ', ASCII_SIGMA2) => 'ς',
+            ('σ', ASCII_SIGMA3) => 'ϲ',
+            ('Σ', ASCII_SIGMA1) => 'Σ',
+            ('Σ', ASCII_SIGMA2) => 'Σ',
+            ('Σ', ASCII_SIGMA3) => 'Ϲ',
+            (_, _) => 0 as char,
+        }
+    }
+
+    #[test]
+    fn accent_table_matches_match() {
+        assert!(ACCENTS
+            .windows(2)
+            .all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1)));
+        for &(c, _, _) in ACCENTS {
+            for accents in 0..0x400 {
+                assert_eq!(
+                    apply_accent(c, accents),
+                    match_accent(c, accents),
+                    "{} {:#x}",
+                    c,
+                    accents
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn accent_table_matches_unicode() {
+        use unicode_normalization::UnicodeNormalization;
+        // In canonical order, the iota subscript sorts after the others.
+        let marks = [
+            (ASCII_DIAERESIS, '\u{308}'),
+            (ASCII_SMOOTH, '\u{313}'),
+            (ASCII_ROUGH, '\u{314}'),
+            (ASCII_ACUTE, '\u{301}'),
+            (ASCII_GRAVE, '\u{300}'),
+            (ASCII_CIRCUMFLEX, '\u{342}'),
+            (ASCII_IOTA, '\u{345}'),
+        ];
+        let options = Converter::new(Type::TLG);
+        let mut mismatches = Vec::new();
+        for c in "αεηιοωυρΑΕΗΙΟΩΥΡ".chars() {
+            for accents in 1..0x80 {
+                let mut expected = c.to_string();
+                for (bit, mark) in marks {
+                    if accents & bit != 0 {
+                        expected.push(mark);
+                    }
+                }
+                let mut word = String::new();
+                let mut letter = Letter {
+                    c,
+                    accents,
+                    index: 0,
+                    final_sigma: false,
+                };
+                if push_letter(&mut word, &options, &mut letter, false, &mut Err).is_ok() {
+                    let actual: String = word.nfd().collect();
+                    let expected: String = expected.nfd().collect();
+                    if actual != expected {
+                        mismatches.push((c, accents, word));
+                    }
+                }
+            }
+        }
+        assert!(mismatches.is_empty(), "{:?}", mismatches);
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(to_greek_bytes(b"", Type::TLG).unwrap(), "");
+        assert_eq!(
+            to_greek_bytes(b"kai\\ qeo/s", Type::TLG).unwrap(),
+            "καὶ θεός"
+        );
+        assert_eq!(
+            to_greek_bytes(b"qe\xffo/s", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\u{ff}', 2)
+        );
+        assert_eq!(
+            to_greek_bytes("dε".as_bytes(), Type::Default).unwrap_err(),
+            to_greek("dε", Type::Default).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(to_greek("ti/ ;", Type::TLG).unwrap(), "τί\u{37e}");
+        assert_eq!(to_greek("ti/;", Type::TLG).unwrap(), "τί\u{37e}");
+        assert_eq!(
+            to_greek("kai\\ e)/legen au)toi=s:", Type::TLG).unwrap(),
+            "καὶ ἔλεγεν αὐτοῖς\u{387}"
+        );
+        assert_eq!(
+            to_greek("ei)=pen , ti/ poiei=s ;", Type::TLG).unwrap(),
+            "εἶπεν, τί ποιεῖς\u{37e}"
+        );
+        assert_eq!(to_greek("a)lla\\ . . .", Type::TLG).unwrap(), "ἀλλὰ...");
+        assert!(to_greek("ti/?", Type::TLG).is_err());
+    }
+
+    // Convert every letter, with every combination of breathing, accent,
+    // diaeresis, iota subscript and length mark, into a table with one
+    // conversion on each line.
+    fn mappings() -> String {
+        let mut table = String::new();
+        let mut marks: Vec<String> = Vec::new();
+        for breathing in ["", ")", "("] {
+            for accent in ["", "/", "\\", "="] {
+                for diaeresis in ["", "+"] {
+                    for iota in ["", "|"] {
+                        marks.push([breathing, accent, diaeresis, iota].concat());
+                    }
+                }
+            }
+        }
+        for (version, name) in [
+            (Type::Default, "default"),
+            (Type::TLG, "tlg"),
+            (Type::Perseus, "perseus"),
+        ] {
+            let mut words: Vec<String> = Vec::new();
+            for c in b'a'..=b'z' {
+                if greek_letter(c, version).is_none() {
+                    continue;
+                }
+                let c = c as char;
+                let capital = match version {
+                    Type::Default => {
+                        |c: char, marks: &str| format!("{}{}", c.to_ascii_uppercase(), marks)
+                    }
+                    Type::TLG | Type::Perseus => |c: char, marks: &str| format!("*{}{}", marks, c),
+                };
+                if !matches!(c, 'a' | 'e' | 'h' | 'i' | 'o' | 'u' | 'w' | 'r') {
+                    words.push(format!("{}", c));
+                    words.push(capital(c, ""));
+                    continue;
+                }
+                for m in &marks {
+                    words.push(format!("{}{}", c, m));
+                    words.push(capital(c, m));
+                    if matches!(c, 'a' | 'i' | 'u') {
+                        for length in ["%26", "%27"] {
+                            words.push(format!("{}{}{}", c, length, m));
+                            words.push(capital(c, &format!("{}{}", length, m)));
+                        }
+                    }
+                }
+            }
+            for sigma in ["s1", "s2", "s3", "es", "es1", "es2", "es3"] {
+                words.push(sigma.to_string());
+            }
+            if version != Type::Default {
+                for n in ["#1", "#2", "#3", "#5", "*#1", "*#2", "*#3", "*#5"] {
+                    words.push(n.to_string());
+                }
+            }
+            for word in words {
+                let greek = match to_greek(&word, version) {
+                    Ok(greek) => greek,
+                    Err(e) => format!("{:?}", e),
+                };
+                table.push_str(&format!("{}\t{}\t{}\n", name, word, greek));
+            }
+        }
+        table
+    }
+
+    // Every conversion is checked against the table in tests/mappings.txt,
+    // so that any change to the output is made on purpose. Run the tests
+    // with UPDATE_MAPPINGS=1 to write the table again after such a change.
+    #[test]
+    fn all_mappings() {
+        let table = mappings();
+        if std::env::var_os("UPDATE_MAPPINGS").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/mappings.txt");
+            std::fs::write(path, &table).unwrap();
+            return;
+        }
+        let expected = include_str!("../tests/mappings.txt");
+        for (line, expected) in table.lines().zip(expected.lines()) {
+            assert_eq!(line, expected);
+        }
+        assert_eq!(table.lines().count(), expected.lines().count());
+    }
+
+    #[test]
+    fn letter_tables() {
+        for version in [Type::Default, Type::TLG, Type::Perseus] {
+            for c in 0..=255 {
+                assert_eq!(
+                    lookup_greek_letter(c, version),
+                    greek_letter_of(c, version),
+                    "{:?} {}",
+                    version,
+                    c
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn basic_greek() {
+        let converter = Converter::new(Type::TLG).with_basic_greek(true);
+        assert_eq!(converter.to_greek("e)/").unwrap(), "ε\u{313}\u{301}");
+        assert_eq!(
+            converter.to_greek("w(=|").unwrap(),
+            "ω\u{314}\u{342}\u{345}"
+        );
+        assert_eq!(converter.to_greek("a%27").unwrap(), "α\u{304}");
+        assert_eq!(
+            converter.to_greek("a)ll' e)gw/").unwrap(),
+            "α\u{313}λλ\u{2019} ε\u{313}γω\u{301}"
+        );
+        let words = mappings();
+        for line in words.lines() {
+            let mut fields = line.split('\t');
+            let name = fields.next().unwrap();
+            let word = fields.next().unwrap();
+            let version: Type = name.parse().unwrap();
+            let converter = Converter::new(version).with_basic_greek(true);
+            let Ok(greek) = converter.to_greek(word) else {
+                continue;
+            };
+            assert!(
+                greek
+                    .chars()
+                    .all(|c| !('\u{1f00}'..='\u{1fff}').contains(&c)),
+                "{} {}",
+                word,
+                greek
+            );
+            let back = to_betacode(&greek, version).unwrap();
+            assert_eq!(converter.to_greek(&back).unwrap(), greek);
+        }
+        let greek = converter
+            .to_greek("*)en a)rxh=| h)=n o( lo/gos: a)p' au)tou=")
+            .unwrap();
+        assert!(greek
+            .chars()
+            .all(|c| !('\u{1f00}'..='\u{1fff}').contains(&c)));
+        assert_eq!(
+            to_betacode(&greek, Type::TLG).unwrap(),
+            "*)en a)rxh=| h)=n o( lo/gos: a)p' au)tou="
+        );
+    }
+
+    #[test]
+    fn with_next() {
+        for version in [Type::Default, Type::TLG] {
+            assert_eq!(to_greek_with_next("qeo/s", version, None).unwrap(), "θεός");
+            assert_eq!(
+                to_greek_with_next("qeo/s", version, Some(' ')).unwrap(),
+                "θεός"
+            );
+            assert_eq!(
+                to_greek_with_next("qeo/s", version, Some(',')).unwrap(),
+                "θεός"
+            );
+            assert_eq!(
+                to_greek_with_next("qeo/s", version, Some('a')).unwrap(),
+                "θεόσ"
+            );
+            assert_eq!(
+                to_greek_with_next("kai\\ s", version, Some('o')).unwrap(),
+                "καὶ σ"
+            );
+            assert_eq!(to_greek_with_next("s2", version, Some('a')).unwrap(), "ς");
+            assert_eq!(to_greek_with_next("s ", version, Some('a')).unwrap(), "ς");
+            assert_eq!(to_greek_with_next("", version, Some('a')).unwrap(), "");
+        }
+        // Converting two pieces gives the same Greek as converting them at
+        // once, when the text is split before a letter or space.
+        let text = "lo/gos o( qeou=";
+        for split in 0..=text.len() {
+            let (first, rest) = text.split_at(split);
+            if rest.starts_with(|c: char| !c.is_ascii_alphabetic() && c != ' ') {
+                continue;
+            }
+            let greek = to_greek_with_next(first, Type::TLG, rest.chars().next()).unwrap()
+                + &to_greek(rest, Type::TLG).unwrap();
+            assert_eq!(greek.replace(' ', ""), "λόγοςὁθεοῦ", "{}", split);
+        }
+        let medial = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(medial.to_greek_with_next("qeo/s", None).unwrap(), "θεόσ");
+        assert_eq!(
+            to_greek_with_next("qeo/s!", Type::TLG, None).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 5)
+        );
+    }
+
+    #[test]
+    fn diaeresis() {
+        for version in [Type::Default, Type::TLG] {
+            // Without a diaeresis the two vowels are a diphthong, and with
+            // one they are two letters, the iota carrying the diaeresis.
+            assert_eq!(to_greek("ai", version).unwrap(), "αι");
+            assert_eq!(to_greek("ai+", version).unwrap(), "αϊ");
+            assert_eq!(to_greek_nfd("ai+", version).unwrap(), "αι\u{308}");
+            assert_eq!(to_greek("ou+", version).unwrap(), "οϋ");
+            assert_eq!(to_greek("ai+/", version).unwrap(), "αΐ");
+            assert_eq!(to_greek("ei+=", version).unwrap(), "εῗ");
+            assert_eq!(to_greek("u+=", version).unwrap(), "ῧ");
+            assert_eq!(to_greek("a)u+pnos", version).unwrap(), "ἀϋπνος");
+            // Only iota and upsilon take a diaeresis.
+            assert_eq!(
+                to_greek("a+i", version).unwrap_err(),
+                ConversionError::UnexpectedAccent('α', 0)
+            );
+            assert_eq!(to_betacode("αι", version).unwrap(), "ai");
+            assert_eq!(to_betacode("αϊ", version).unwrap(), "ai+");
+            assert_eq!(to_betacode("αι\u{308}", version).unwrap(), "ai+");
+            assert_eq!(to_betacode("εῗ", version).unwrap(), "ei+=");
+        }
+        assert_eq!(to_greek("*ai+", Type::TLG).unwrap(), "Αϊ");
+        assert_eq!(to_greek("*i+=", Type::TLG).unwrap(), "Ι\u{308}\u{342}");
+        assert_eq!(to_betacode("Αϊ", Type::TLG).unwrap(), "*ai+");
+    }
+
+    #[test]
+    fn raised_dot() {
+        let text = "kai\\ e)/legen au)toi=s: e)gw/ ei)mi";
+        let ano_teleia = "καὶ ἔλεγεν αὐτοῖς\u{387} ἐγώ εἰμι";
+        let middle_dot = "καὶ ἔλεγεν αὐτοῖς\u{b7} ἐγώ εἰμι";
+        assert_eq!(to_greek(text, Type::TLG).unwrap(), ano_teleia);
+        let converter = Converter::new(Type::TLG);
+        assert_eq!(
+            converter.with_raised_dot('\u{387}').to_greek(text).unwrap(),
+            ano_teleia
+        );
+        let middle = converter.with_raised_dot('\u{b7}');
+        assert_eq!(middle.to_greek(text).unwrap(), middle_dot);
+        assert_eq!(middle.to_greek("kalo\\n :").unwrap(), "καλὸν\u{b7}");
+        assert_eq!(
+            middle
+                .with_final_acute(true)
+                .to_greek("kalo\\n: kai\\")
+                .unwrap(),
+            "καλόν\u{b7} καὶ"
+        );
+        assert_eq!(
+            converter
+                .with_nfd(true)
+                .with_final_acute(true)
+                .to_greek("kalo\\n:")
+                .unwrap(),
+            "καλο\u{301}ν\u{b7}"
+        );
+        assert_eq!(
+            to_betacode(middle_dot, Type::TLG).unwrap(),
+            "kai\\ e)/legen au)toi=s: e)gw/ ei)mi"
+        );
+    }
+
+    #[test]
+    fn stripped() {
+        assert_eq!(to_greek_stripped("qeo/s", Type::TLG).unwrap(), "θεος");
+        assert_eq!(
+            to_greek_stripped("*(/hrw| kai\\ a)i+/dhs a)ph=lqen", Type::TLG).unwrap(),
+            "Ηρω και αιδης απηλθεν"
+        );
+        assert_eq!(to_greek_stripped("r(a%27)/", Type::TLG).unwrap(), "ρα");
+        assert_eq!(to_greek_stripped("*)=w|", Type::TLG).unwrap(), "Ω");
+        assert_eq!(
+            to_greek_stripped("a)p' ou)k", Type::TLG).unwrap(),
+            "απ᾽ ουκ"
+        );
+        assert_eq!(
+            to_greek_stripped("#awka#", Type::TLG).unwrap(),
+            "\u{375}αωκα\u{2b9}"
+        );
+        assert_eq!(
+            to_greek_stripped("E)n a)rch=|", Type::Default).unwrap(),
+            "Εν αρχη"
+        );
+        let medial = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(medial.to_greek_stripped("qeo/s").unwrap(), "θεοσ");
+        let lunate = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Lunate);
+        assert_eq!(lunate.to_greek_stripped("qeo/s").unwrap(), "θεοϲ");
+    }
+
+    #[test]
+    fn editorial_brackets() {
+        let passthrough =
+            Converter::new(Type::TLG).with_punctuation(PunctuationPolicy::Passthrough);
+        assert_eq!(passthrough.to_greek("[qeo/s]").unwrap(), "[θεός]");
+        assert_eq!(
+            passthrough.to_greek("o( [qeo/s] <kai\\>").unwrap(),
+            "ὁ [θεός] <καὶ>"
+        );
+        // A word carries on after a bracket inside it.
+        assert_eq!(passthrough.to_greek("[s]w/mata").unwrap(), "[σ]ώματα");
+        assert_eq!(passthrough.to_greek("lo/go[s]").unwrap(), "λόγο[ς]");
+        assert_eq!(passthrough.to_greek("qeo/[s] kai\\").unwrap(), "θεό[ς] καὶ");
+        assert_eq!(passthrough.to_greek("a)/nqrw[pos]").unwrap(), "ἄνθρω[πος]");
+        assert_eq!(
+            passthrough.to_greek("ba[s]<i>leu/s").unwrap(),
+            "βα[σ]<ι>λεύς"
+        );
+        assert_eq!(passthrough.to_greek("[s][*a]").unwrap(), "[σ][Α]");
+        assert_eq!(passthrough.to_greek("[s] ").unwrap(), "[ς]");
+        assert_eq!(
+            Converter::new(Type::Default)
+                .with_punctuation(PunctuationPolicy::Passthrough)
+                .to_greek("Qeo[s]")
+                .unwrap(),
+            "Θεο[ς]"
+        );
+        // Round brackets are breathings, not editorial brackets.
+        assert_eq!(
+            passthrough.to_greek("(qeo/s)").unwrap_err(),
+            ConversionError::UnexpectedCharacter('(', 0)
+        );
+        assert_eq!(
+            to_greek("[qeo/s]", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('[', 0)
+        );
+    }
+
+    #[test]
+    fn dash() {
+        for version in [Type::Default, Type::TLG] {
+            assert_eq!(
+                to_greek("kai\\ _ w(s ei)=pen _ a)ph=lqen.", version).unwrap(),
+                "καὶ — ὡς εἶπεν — ἀπῆλθεν."
+            );
+            assert_eq!(to_greek("lo/gos_kai\\", version).unwrap(), "λόγος—καὶ");
+            assert_eq!(to_greek("_", version).unwrap(), "—");
+        }
+        // A dash does not change the length marks before or after it.
+        assert_eq!(to_greek("a%27_a%26", Type::TLG).unwrap(), "ᾱ—ᾰ");
+        assert_eq!(
+            to_greek("%_", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('%', 0)
+        );
+        let bar = Converter::new(Type::TLG).with_dash('\u{2015}');
+        assert_eq!(bar.to_greek("a _ b").unwrap(), "α \u{2015} β");
+        assert_eq!(
+            Converter::new(Type::TLG)
+                .with_punctuation(PunctuationPolicy::Passthrough)
+                .to_greek("a _ b")
+                .unwrap(),
+            "α — β"
+        );
+        assert_eq!(
+            to_betacode("καὶ — λόγος ―", Type::TLG).unwrap(),
+            "kai\\ _ lo/gos _"
+        );
+    }
+
+    #[test]
+    fn lossy() {
+        assert_eq!(to_greek_lossy("", Type::TLG), "");
+        assert_eq!(to_greek_lossy("kai\\ qeo/s", Type::TLG), "καὶ θεός");
+        assert_eq!(to_greek_lossy("qeo/s!", Type::TLG), "θεός\u{fffd}");
+        assert_eq!(to_greek_lossy("a/\\bg", Type::TLG), "\u{fffd}βγ");
+        assert_eq!(to_greek_lossy("dεs", Type::Default), "δ\u{fffd}ς");
+        assert_eq!(to_greek_lossy("*a", Type::Default), "\u{fffd}α");
+        assert_eq!(to_greek_lossy("a)p'b", Type::Default), "ἀπ᾽\u{fffd}");
+        assert_eq!(
+            to_greek_lossy("lo/gos ) kai\\", Type::TLG),
+            "λόγος \u{fffd} καὶ"
+        );
+    }
+
+    #[test]
+    fn all_errors() {
+        assert_eq!(
+            to_greek_all_errors("kai\\ qeo/s", Type::TLG),
+            ("καὶ θεός".to_string(), vec![])
+        );
+        let (word, errors) = to_greek_all_errors("a/\\ *b !", Type::Default);
+        assert_eq!(word, "\u{fffd} \u{fffd}β \u{fffd}");
+        assert_eq!(
+            errors,
+            vec![
+                ConversionError::UnexpectedAccent('α', 0),
+                ConversionError::UnexpectedCharacter('*', 4),
+                ConversionError::UnexpectedCharacter('!', 7),
+            ]
+        );
+        let (_, errors) = to_greek_all_errors("qeo/s! lo/gos!", Type::TLG);
+        assert_eq!(
+            errors[0],
+            to_greek("qeo/s! lo/gos!", Type::TLG).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn converter() {
+        let tlg = Converter::new(Type::TLG);
+        assert_eq!(tlg.to_greek("kai\\ qeo/s").unwrap(), "καὶ θεός");
+        assert_eq!(tlg.to_greek_bytes(b"qeo/s").unwrap(), "θεός");
+        assert_eq!(tlg.to_greek_cow("qeo/s").unwrap(), "θεός");
+        assert_eq!(tlg.validate("qeo/s!"), validate("qeo/s!", Type::TLG));
+        assert_eq!(tlg.to_greek_lossy("qeo/s!"), "θεός\u{fffd}");
+
+        let nfd = Converter::new(Type::TLG).with_nfd(true);
+        assert_eq!(nfd.to_greek("qeo/s").unwrap(), "θεο\u{301}ς");
+        let mut word = String::new();
+        nfd.to_greek_into("e)/", &mut word).unwrap();
+        assert_eq!(word, "ε\u{313}\u{301}");
+        assert_eq!(nfd.to_greek_lossy("e)/!"), "ε\u{313}\u{301}\u{fffd}");
+        assert_eq!(nfd.with_nfd(false), tlg);
+    }
+
+    #[test]
+    fn sigma_policy() {
+        let auto = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Auto);
+        let lunate = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Lunate);
+        let medial = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(auto.to_greek("lo/gos").unwrap(), "λόγος");
+        assert_eq!(lunate.to_greek("lo/gos").unwrap(), "λόγοϲ");
+        assert_eq!(medial.to_greek("lo/gos").unwrap(), "λόγοσ");
+        assert_eq!(lunate.to_greek("sw/mas, o(").unwrap(), "ϲώμαϲ, ὁ");
+        assert_eq!(medial.to_greek("sw/mas, o(").unwrap(), "σώμασ, ὁ");
+        assert_eq!(lunate.to_greek("qeo/s1 qeo/s2").unwrap(), "θεόσ θεός");
+        assert_eq!(medial.to_greek("qeo/s2").unwrap(), "θεός");
+        assert_eq!(auto.to_greek("qeo/s3").unwrap(), "θεόϲ");
+        assert_eq!(auto.to_greek("*Q*E*O/*S").unwrap(), "ΘΕΌΣ");
+        assert_eq!(medial.to_greek("*Q*E*O/*S").unwrap(), "ΘΕΌΣ");
+        assert_eq!(lunate.to_greek("*Q*E*O/*S").unwrap(), "ΘΕΌϹ");
+        assert_eq!(lunate.to_greek("*S*W*S*S").unwrap(), "ϹΩϹϹ");
+        assert_eq!(auto.to_greek("*S*W*S*S").unwrap(), "ΣΩΣΣ");
+        assert_eq!(auto.to_greek("*S3").unwrap(), "Ϲ");
+        assert_eq!(lunate.to_greek("*S1").unwrap(), "Σ");
+        let lunate = Converter::new(Type::Default).with_sigma(SigmaPolicy::Lunate);
+        assert_eq!(lunate.to_greek("QEOS").unwrap(), "ΘΕΟϹ");
+    }
+
+    #[test]
+    fn adscript() {
+        let subscript = Converter::new(Type::TLG);
+        let adscript = Converter::new(Type::TLG).with_adscript(true);
+        assert_eq!(subscript.to_greek("a|").unwrap(), "ᾳ");
+        assert_eq!(adscript.to_greek("a|").unwrap(), "αι");
+        assert_eq!(subscript.to_greek("*a|").unwrap(), "ᾼ");
+        assert_eq!(adscript.to_greek("*a|").unwrap(), "ΑΙ");
+        assert_eq!(subscript.to_greek("th=| w)|dh=|").unwrap(), "τῇ ᾠδῇ");
+        assert_eq!(adscript.to_greek("th=| w)|dh=|").unwrap(), "τῆι ὠιδῆι");
+        assert_eq!(adscript.to_greek("*(/w|").unwrap(), "ὭΙ");
+        assert_eq!(
+            adscript.with_nfd(true).to_greek("h=|").unwrap(),
+            "η\u{342}ι"
+        );
+        assert_eq!(
+            adscript.to_greek("e|").unwrap_err(),
+            ConversionError::UnexpectedAccent('ε', 0)
+        );
+    }
+
+    #[test]
+    fn length_marks() {
+        assert_eq!(to_greek("a%27", Type::TLG).unwrap(), "ᾱ");
+        assert_eq!(to_greek("i%26", Type::TLG).unwrap(), "ῐ");
+        assert_eq!(to_greek("*u%27", Type::TLG).unwrap(), "Ῡ");
+        assert_eq!(to_greek("*%26a", Type::TLG).unwrap(), "Ᾰ");
+        assert_eq!(to_greek("a%27/", Type::TLG).unwrap(), "ᾱ\u{301}");
+        assert_eq!(to_greek("a/%27", Type::TLG).unwrap(), "ᾱ\u{301}");
+        assert_eq!(to_greek("a)%27|", Type::TLG).unwrap(), "ᾱ\u{313}\u{345}");
+        assert_eq!(
+            to_greek("kalo%26s", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedAccent('ο', 3)
+        );
+        assert_eq!(
+            to_greek("ta%27%26", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('α', 1)
+        );
+        assert_eq!(
+            to_greek("%27a", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('%', 0)
+        );
+        assert_eq!(
+            to_greek("a%28", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('%', 1)
+        );
+        assert_eq!(
+            Converter::new(Type::TLG)
+                .with_nfd(true)
+                .to_greek("u%27(/")
+                .unwrap(),
+            "υ\u{304}\u{314}\u{301}"
+        );
+        assert_eq!(to_betacode("ᾱ\u{301}", Type::TLG).unwrap(), "a/%27");
+        assert_eq!(to_betacode("Ᾱ\u{313}", Type::TLG).unwrap(), "*)a%27");
+    }
+
+    // The most marks a single vowel can carry: a length, a breathing, an
+    // accent and an iota subscript, or a length, a diaeresis and an accent.
+    #[test]
+    fn stacked_marks() {
+        assert_eq!(
+            to_greek("a%27)/|", Type::TLG).unwrap(),
+            "ᾱ\u{313}\u{301}\u{345}"
+        );
+        assert_eq!(
+            to_greek("*)/a%27|", Type::TLG).unwrap(),
+            "Ᾱ\u{313}\u{301}\u{345}"
+        );
+        assert_eq!(to_greek("i%27+/", Type::TLG).unwrap(), "ῑ\u{308}\u{301}");
+        assert_eq!(to_greek("u%26(=", Type::TLG).unwrap(), "ῠ\u{314}\u{342}");
+        assert_eq!(
+            to_betacode("ᾱ\u{313}\u{301}\u{345}", Type::TLG).unwrap(),
+            "a)/%27|"
+        );
+        assert_eq!(to_betacode("ῑ\u{308}\u{301}", Type::TLG).unwrap(), "i+/%27");
+        assert_eq!(
+            to_greek("a%27)/|)", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('α', 0)
+        );
+        assert_eq!(Accents::all().bits() & ASCII_REPEATED, 0);
+    }
+
+    #[test]
+    fn skip_markup() {
+        let options = Converter::new(Type::TLG).with_skip_markup(true);
+        assert_eq!(
+            options.to_greek("$1*)en a)rxh=|$ h)=n o( lo/gos").unwrap(),
+            "Ἐν ἀρχῇ ἦν ὁ λόγος"
+        );
+        assert_eq!(
+            options
+                .to_greek("kai\\ $1qeo\\s$ h)=n &1o( lo/gos&")
+                .unwrap(),
+            "καὶ θεὸς ἦν ὁ λόγος"
+        );
+        assert_eq!(options.to_greek("lo$1go$s@1").unwrap(), "λογος");
+        assert_eq!(options.to_greek("\"3a)lla\"3").unwrap(), "ἀλλα");
+        assert_eq!(
+            Converter::new(Type::TLG).to_greek("$1lo/gos$").unwrap_err(),
+            ConversionError::UnexpectedCharacter('$', 0)
+        );
+    }
+
+    #[test]
+    fn accents() {
+        let mut accents = Accents::empty();
+        assert!(accents.is_empty());
+        for c in "/\\()|+=^123".bytes() {
+            assert!(!is_valid_betacode_symbol(c).is_empty());
+            accents |= is_valid_betacode_symbol(c);
+        }
+        assert!(is_valid_betacode_symbol(b'a').is_empty());
+        accents.insert(Accents::MACRON | Accents::BREVE);
+        assert_eq!(accents, Accents::all());
+        accents.remove(Accents::MACRON);
+        assert!(!accents.contains(Accents::MACRON | Accents::BREVE));
+        assert_eq!(accents & Accents::BREVE, Accents::BREVE);
+        assert_eq!(Accents::from_bits(0x1000), None);
+        assert_eq!(Accents::from_bits_truncate(0x1001), Accents::ACUTE);
+        assert_eq!(
+            u16::from(Accents::ROUGH | Accents::GRAVE),
+            ASCII_ROUGH_GRAVE
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn greek_writer() {
+        use std::io::Write;
+        for (version, text) in [
+            (
+                Type::Default,
+                "  E)n a)rch=| h)=n o( lo/gov , kai\\ o( lo/gov h)=n pro\\v to\\n qeo/n: ",
+            ),
+            (
+                Type::TLG,
+                "  *)en a)rxh=| h)=n o( lo/gos , kai\\ o( lo/gos h)=n pro\\s to\\n qeo/n: ",
+            ),
+        ] {
+            let mut writer = GreekWriter::new(Vec::new(), Converter::new(version));
+            for b in text.bytes() {
+                assert_eq!(writer.write(&[b]).unwrap(), 1);
+            }
+            let greek = writer.into_inner().unwrap();
+            assert_eq!(
+                std::str::from_utf8(&greek).unwrap(),
+                to_greek(text, version).unwrap()
+            );
+        }
+
+        let mut writer = GreekWriter::new(Vec::new(), Converter::new(Type::TLG));
+        writer.write_all(b"a").unwrap();
+        writer.write_all(b")/s").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref(), "ἄς".as_bytes());
+
+        let mut writer = GreekWriter::new(Vec::new(), Converter::new(Type::TLG));
+        writer.write_all(b"lo/gos ").unwrap();
+        let e = writer.write_all(b"lo!gos kai").unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            e.into_inner().unwrap().downcast_ref::<ConversionError>(),
+            Some(&ConversionError::UnexpectedCharacter('!', 9))
+        );
+        // The bad buffer is not kept, so the words before it still convert.
+        writer.write_all(b"kai").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.get_ref(), "λόγος και".as_bytes());
+
+        // A writer that fails until it is allowed to write
+        struct Failing(Vec<u8>, bool);
+        impl std::io::Write for Failing {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if !self.1 {
+                    return Err(std::io::ErrorKind::Other.into());
+                }
+                self.0.write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut writer = GreekWriter::new(Failing(Vec::new(), true), Converter::new(Type::TLG));
+        writer.write_all(b"lo/gos ").unwrap();
+        writer.inner.1 = false;
+        assert!(writer.write(b"kai\\ ").is_err());
+        assert!(writer.flush().is_err());
+        writer.inner.1 = true;
+        assert_eq!(writer.write(b"kai\\ ").unwrap(), 5);
+        let greek = writer.into_inner().unwrap().0;
+        assert_eq!(std::str::from_utf8(&greek).unwrap(), "λόγος καὶ");
+    }
+
+    #[test]
+    fn final_acute() {
+        let options = Converter::new(Type::TLG).with_final_acute(true);
+        assert_eq!(
+            options
+                .to_greek("kai\\ ei)=pen au)tw=| to\\n lo/gon kalo\\n.")
+                .unwrap(),
+            "καὶ εἶπεν αὐτῷ τὸν λόγον καλόν."
+        );
+        assert_eq!(
+            options
+                .to_greek("a)gaqo\\n, kai\\ kalo/n: ti/ ga\\r;")
+                .unwrap(),
+            "ἀγαθὸν, καὶ καλόν\u{387} τί γάρ\u{37e}"
+        );
+        assert_eq!(options.to_greek("o(\\ .").unwrap(), "ὅ.");
+        // A question mark or raised dot ends a clause, and an acute accent
+        // before it is kept.
+        assert_eq!(options.to_greek("ti/;").unwrap(), "τί\u{37e}");
+        assert_eq!(options.to_greek("ti\\;").unwrap(), "τί\u{37e}");
+        assert_eq!(options.to_greek("kalo/n:").unwrap(), "καλόν\u{387}");
+        assert_eq!(options.to_greek("ti/ kai\\;").unwrap(), "τί καί\u{37e}");
+        assert_eq!(options.to_greek("kai\\").unwrap(), "καὶ");
+        assert_eq!(
+            options.with_nfd(true).to_greek("kalo\\n.").unwrap(),
+            "καλο\u{301}ν."
+        );
+        assert_eq!(
+            Converter::new(Type::TLG).to_greek("kalo\\n.").unwrap(),
+            "καλὸν."
+        );
+
+        // Punctuation written apart from the last word still ends its
+        // clause when converting a word at a time.
+        assert_eq!(options.to_greek("kai\\ .").unwrap(), "καί.");
+        for text in [
+            "kai\\ .",
+            "kai\\ ; ti/",
+            "kalo\\n\n: kai\\ ei)=pen",
+            "kai\\ , kalo\\n .",
+        ] {
+            assert_word_at_a_time(options, text);
+        }
+    }
+
+    #[test]
+    fn greek_chars() {
+        for (version, text) in [
+            (
+                Type::Default,
+                "  E)n a)rch=| h)=n o( lo/gov , kai\\ o( lo/gov h)=n pro\\v to\\n qeo/n: ",
+            ),
+            (
+                Type::TLG,
+                "*)en a)rxh=|   h)=n\to( lo/gos, kai\\ o( lo/gos h)=n pro\\s to\\n qeo/n",
+            ),
+            (Type::TLG, ""),
+            (Type::TLG, "   "),
+        ] {
+            let chars = GreekChars::new(text.bytes(), Converter::new(version));
+            let greek: Result<String, ConversionError> = chars.collect();
+            assert_eq!(greek.unwrap(), to_greek(text, version).unwrap());
+        }
+        let options = Converter::new(Type::TLG);
+        let mut chars = GreekChars::new("lo/gos lo!gos kai".bytes(), options);
+        let greek: String = chars.by_ref().map_while(Result::ok).collect();
+        assert_eq!(greek, "λόγος");
+        assert_eq!(chars.next(), None);
+        let mut chars = GreekChars::new("lo/gos lo!gos".bytes(), options);
+        assert_eq!(
+            chars.find_map(Result::err),
+            Some(ConversionError::UnexpectedCharacter('!', 9))
+        );
+    }
+
+    #[test]
+    fn hyphens() {
+        assert_eq!(to_greek("lo/go-\ns", Type::TLG).unwrap(), "λόγος");
+        assert_eq!(to_greek("pro/s-  \n  wpon", Type::TLG).unwrap(), "πρόσωπον");
+        assert_eq!(to_greek("pro/swpon", Type::TLG).unwrap(), "πρόσωπον");
+        assert_eq!(to_greek("o( pro/s-", Type::TLG).unwrap(), "ὁ πρόσ");
+        assert_eq!(
+            to_greek("*)ihsou=s-\nxristo/s", Type::TLG).unwrap(),
+            "Ἰησοῦσχριστός"
+        );
+        let keep = Converter::new(Type::TLG).with_keep_hyphens(true);
+        assert_eq!(keep.to_greek("pro/s-\nwpon").unwrap(), "πρόσ-\nωπον");
+        assert_eq!(keep.to_greek("pro/s-").unwrap(), "πρόσ-");
+        assert_eq!(
+            to_greek("pro/s- wpon", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('-', 5)
+        );
+        assert_eq!(
+            to_greek("o( -\nlo/gos", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('-', 3)
+        );
 
-    if size == 0 {
-        return Ok("".to_string());
+        let options = Converter::new(Type::TLG);
+        let chars = GreekChars::new("kai\\ pro/s-\nwpon".bytes(), options);
+        let greek: Result<String, ConversionError> = chars.collect();
+        assert_eq!(greek.unwrap(), "καὶ πρόσωπον");
+        let chars = GreekChars::new("lo/go-\ns kai\\".bytes(), options.with_keep_hyphens(true));
+        let greek: Result<String, ConversionError> = chars.collect();
+        assert_eq!(greek.unwrap(), "λόγο-\nς καὶ");
+        let text = "a- \nb a- b";
+        let chars = GreekChars::new(text.bytes(), options.with_keep_hyphens(true));
+        let greek: Result<String, ConversionError> = chars.collect();
+        assert_eq!(greek, options.with_keep_hyphens(true).to_greek(text));
     }
 
-    // Trim whitespace from start
-    loop {
-        if i == size {
-            return Ok("".to_string());
-        }
-        if !is_ascii_whitespace(text[i]) {
-            break;
+    #[test]
+    fn write() {
+        let mut greek = String::from("> ");
+        write_greek("*)en a)rxh=| h)=n o( lo/gos", Type::TLG, &mut greek).unwrap();
+        assert_eq!(greek, "> Ἐν ἀρχῇ ἦν ὁ λόγος");
+        let mut betacode = String::new();
+        write_betacode("Ἐν ἀρχῇ ἦν ὁ λόγος", Type::TLG, &mut betacode).unwrap();
+        assert_eq!(betacode, "*)en a)rxh=| h)=n o( lo/gos");
+        let mut greek = String::new();
+        assert_eq!(
+            write_greek("lo/g!os", Type::TLG, &mut greek),
+            Err(ConversionError::UnexpectedCharacter('!', 4))
+        );
+        let mut greek = String::new();
+        let options = Converter::new(Type::TLG).with_nfd(true);
+        options.write_greek("h)=n", &mut greek).unwrap();
+        assert_eq!(greek, options.to_greek("h)=n").unwrap());
+        let mut greek = String::new();
+        let options = Converter::new(Type::TLG).with_final_acute(true);
+        options.write_greek("kalo\\n.", &mut greek).unwrap();
+        assert_eq!(greek, "καλόν.");
+
+        // A writer that fails after a few characters
+        struct Short(usize);
+        impl fmt::Write for Short {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                if self.0 < s.len() {
+                    return Err(fmt::Error);
+                }
+                self.0 -= s.len();
+                Ok(())
+            }
         }
-        i += 1;
+        assert_eq!(
+            write_greek("lo/gos", Type::TLG, &mut Short(4)),
+            Err(ConversionError::Write)
+        );
+        assert_eq!(
+            Converter::new(Type::TLG)
+                .with_final_acute(true)
+                .write_greek("lo/gos", &mut Short(4)),
+            Err(ConversionError::Write)
+        );
+        assert_eq!(
+            write_betacode("λόγος", Type::TLG, &mut Short(4)),
+            Err(ConversionError::Write)
+        );
+        assert_eq!(
+            write_greek("lo/g!os", Type::TLG, &mut Short(4)),
+            Err(ConversionError::UnexpectedCharacter('!', 4))
+        );
+        assert_eq!(write_greek("lo/gos", Type::TLG, &mut Short(12)), Ok(()));
+        assert_eq!(ConversionError::Write.byte_index(), 0);
     }
 
-    // Trim whitespace from end
-    loop {
-        if i == size {
-            return Ok("".to_string());
-        }
-        if !is_ascii_whitespace(text[size - 1]) {
-            break;
-        }
-        size -= 1;
-        continue;
+    #[test]
+    fn punctuation_policy() {
+        let strict = Converter::new(Type::TLG);
+        let passthrough =
+            Converter::new(Type::TLG).with_punctuation(PunctuationPolicy::Passthrough);
+        assert_eq!(
+            strict.to_greek("lo/gos -- kai\\").unwrap_err(),
+            ConversionError::UnexpectedCharacter('-', 7)
+        );
+        assert_eq!(
+            passthrough.to_greek("lo/gos -- kai\\").unwrap(),
+            "λόγος -- καὶ"
+        );
+        assert_eq!(passthrough.to_greek("[lo/gos] !").unwrap(), "[λόγος] !");
+        assert_eq!(
+            passthrough.to_greek("<kai\\>, \"qeo/s\"").unwrap(),
+            "<καὶ>, \"θεός\""
+        );
+        // Brackets are breathings, so a parenthetical aside is not kept.
+        assert_eq!(passthrough.to_greek("a(").unwrap(), "ἁ");
+        assert_eq!(
+            passthrough.to_greek("kai\\ (lo/gos)").unwrap_err(),
+            ConversionError::UnexpectedCharacter('(', 5)
+        );
+        assert_eq!(passthrough.to_greek("lo/go-\ns").unwrap(), "λόγος");
+        assert_eq!(passthrough.to_greek("a#").unwrap(), "α\u{374}");
+        assert_eq!(
+            passthrough.to_greek("lo/gos é").unwrap_err(),
+            ConversionError::UnexpectedCharacter('\u{c3}', 7)
+        );
     }
 
-    // Read a character and any accents following it
-    let mut current: char = 0 as char;
-    let mut current_index: usize = 0;
-    let mut accents: u16 = 0;
-    let mut uppercase: bool = false;
+    #[test]
+    fn chars() {
+        assert_eq!(
+            to_greek_chars("qeo/s", Type::TLG).unwrap(),
+            vec!['θ', 'ε', 'ό', 'ς']
+        );
+        assert_eq!(to_greek_chars("", Type::TLG).unwrap(), vec![]);
+        assert_eq!(
+            to_greek_chars("qeo/s!", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 5)
+        );
+        for options in [
+            Converter::new(Type::TLG),
+            Converter::new(Type::TLG).with_nfd(true),
+            Converter::new(Type::TLG).with_final_acute(true),
+        ] {
+            let text = "*)en a)rxh=| h)=n o( lo/gos, kai\\ qeo\\s h)=n o( lo/gos.";
+            let chars: String = options.to_greek_chars(text).unwrap().into_iter().collect();
+            assert_eq!(chars, options.to_greek(text).unwrap());
+        }
+    }
 
-    loop {
-        if i == size {
-            break;
+    #[test]
+    fn words_iter() {
+        let input = "  *)en a)rxh=| h)=n o( lo/gos,\n\tkai\\ o( lo/gos h)=n pro\\s to\\n qeo/n. ";
+        for version in [Type::TLG, Type::Perseus] {
+            let greek: Vec<String> = words(input, version).map(Result::unwrap).collect();
+            assert_eq!(greek.join(" "), to_greek(input, version).unwrap());
+            assert_eq!(greek.len(), 12);
         }
-        let mut c = text[i];
-        if c == b'*' {
-            if version == Type::TLG {
-                uppercase = true;
-                i += 1;
-                continue;
+        assert_eq!(words("", Type::TLG).count(), 0);
+        assert_eq!(words(" \n ", Type::TLG).count(), 0);
+        let mut iter = words("lo/gos lo!gos kai\\ *", Type::TLG);
+        assert_eq!(iter.next(), Some(Ok("λόγος".to_string())));
+        assert_eq!(
+            iter.next(),
+            Some(Err(ConversionError::UnexpectedCharacter('!', 9)))
+        );
+        assert_eq!(iter.next(), Some(Ok("καὶ".to_string())));
+        assert_eq!(
+            iter.next(),
+            Some(Err(ConversionError::UnexpectedCharacter('*', 19)))
+        );
+        assert_eq!(iter.next(), None);
+        let converter = Converter::new(Type::TLG).with_token_errors(true);
+        assert_eq!(
+            converter.words("a b!").nth(1).unwrap().unwrap_err(),
+            ConversionError::InvalidToken {
+                token: "b!".to_string(),
+                start: 2,
+                end: 4,
+                reason: Box::new(ConversionError::UnexpectedCharacter('!', 3)),
             }
-            return Err(ConversionError::UnexpectedCharacter(c as char, i));
-        }
-        if c > 127 {
-            // Unicode sequences should not appear
-            // in ascii betacode sequences
-            return Err(ConversionError::UnexpectedCharacter(c as char, i));
+        );
+    }
+
+    #[test]
+    fn token() {
+        let input = "  *)en a)rxh=|\n\th)=n ";
+        let mut rest = input;
+        let mut words = Vec::new();
+        while !rest.is_empty() {
+            let (word, read) = to_greek_token(rest, Type::TLG).unwrap();
+            words.push(word);
+            rest = &rest[read..];
         }
-        if version == Type::TLG {
-            if uppercase == true {
-                if c >= b'a' && c <= b'z' {
-                    c -= b'a' - b'A'
-                }
-                uppercase = false
-            } else {
-                if c >= b'A' && c <= b'Z' {
-                    c += b'a' - b'A'
-                }
-            }
+        assert_eq!(words, vec!["Ἐν", "ἀρχῇ", "ἦν"]);
+        assert_eq!(to_greek_token("", Type::TLG).unwrap(), (String::new(), 0));
+        assert_eq!(to_greek_token(" ", Type::TLG).unwrap(), (String::new(), 1));
+        assert_eq!(
+            to_greek_token("lo/gos, kai\\", Type::TLG).unwrap(),
+            ("λόγος,".to_string(), 8)
+        );
+        assert_eq!(
+            to_greek_token(" lo!gos kai\\", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 3)
+        );
+        assert_eq!(
+            to_greek_token("kai\\ lo!gos", Type::TLG).unwrap(),
+            ("καὶ".to_string(), 5)
+        );
+    }
+
+    #[test]
+    fn leading_accents() {
+        let lenient = Converter::new(Type::TLG).with_leading_accents(true);
+        assert_eq!(lenient.to_greek(")a").unwrap(), "ἀ");
+        assert_eq!(lenient.to_greek("(a").unwrap(), "ἁ");
+        assert_eq!(lenient.to_greek("/a").unwrap(), "ά");
+        assert_eq!(lenient.to_greek(")/a").unwrap(), "ἄ");
+        assert_eq!(lenient.to_greek(")a/").unwrap(), "ἄ");
+        assert_eq!(lenient.to_greek("*)a").unwrap(), "Ἀ");
+        assert_eq!(
+            lenient.to_greek("kai\\ )e)n").unwrap_err(),
+            ConversionError::UnexpectedAccent('ε', 6)
+        );
+        assert_eq!(lenient.to_greek("kai\\ )/en").unwrap(), "καὶ ἔν");
+        assert_eq!(
+            Converter::new(Type::Default)
+                .with_leading_accents(true)
+                .to_greek("(O")
+                .unwrap(),
+            "Ὁ"
+        );
+        // Accents must be followed by a letter.
+        assert_eq!(
+            lenient.to_greek(") a").unwrap_err(),
+            ConversionError::UnexpectedCharacter(')', 0)
+        );
+        assert_eq!(
+            lenient.to_greek(")").unwrap_err(),
+            ConversionError::UnexpectedCharacter(')', 0)
+        );
+        assert_eq!(
+            lenient.to_greek("),").unwrap_err(),
+            ConversionError::UnexpectedCharacter(')', 0)
+        );
+        // Accents before a letter are an error by default.
+        for betacode in [")a", "(a", "/a"] {
+            assert!(to_greek(betacode, Type::TLG).is_err());
+            assert!(to_greek(betacode, Type::Default).is_err());
         }
-        let l = lookup_greek_letter(c, version);
-        if l != 0 as char {
-            if current != 0 as char {
-                // We encountered the next letter, if we just read a previous
-                // letter, push it onto the return string.
-                let e = apply_accent(current, accents);
-                if e > 0 as char {
-                    word.push(e)
-                } else {
-                    return Err(ConversionError::UnexpectedAccent(
-                        current as char,
-                        current_index,
-                    ));
-                }
-            }
-            // The start of a letter sequence
-            current = l;
-            current_index = i;
-            accents = 0;
-            i += 1;
-            continue;
+    }
+
+    #[test]
+    fn case() {
+        assert_eq!(to_greek_upper("qeo/s", Type::TLG).unwrap(), "ΘΕΌΣ");
+        assert_eq!(to_greek_upper("Qeo/s", Type::Default).unwrap(), "ΘΕΌΣ");
+        assert_eq!(to_greek_upper("a)/nqrwpos", Type::TLG).unwrap(), "ἌΝΘΡΩΠΟΣ");
+        assert_eq!(to_greek_upper("a|", Type::TLG).unwrap(), "ΑΙ");
+        assert_eq!(to_greek_upper("s3", Type::TLG).unwrap(), "Ϲ");
+        assert_eq!(
+            to_greek_upper("qeo/s!", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 5)
+        );
+        assert_eq!(
+            to_greek_lower("*O *L*O/*G*O*S *S*O*S, *S.", Type::TLG).unwrap(),
+            "ο λόγος σος, σ."
+        );
+        assert_eq!(
+            to_greek_lower("O LO/GOS", Type::Default).unwrap(),
+            "ο λόγος"
+        );
+        assert_eq!(
+            to_greek_lower("*)/ANQRWPOS", Type::TLG).unwrap(),
+            "ἄνθρωπος"
+        );
+        assert_eq!(to_greek_lower("*Q*E*O/*S", Type::TLG).unwrap(), "θεός");
+        let nfd = Converter::new(Type::TLG).with_nfd(true);
+        assert_eq!(nfd.to_greek_lower("*)/A*S").unwrap(), "α\u{313}\u{301}ς");
+        assert_eq!(nfd.to_greek_lower("*S*)/A").unwrap(), "σα\u{313}\u{301}");
+        let medial = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(medial.to_greek_lower("*Q*E*O/*S").unwrap(), "θεόσ");
+        let lunate = Converter::new(Type::TLG).with_sigma(SigmaPolicy::Lunate);
+        assert_eq!(lunate.to_greek_lower("*Q*E*O/*S").unwrap(), "θεόϲ");
+    }
+
+    #[test]
+    fn rough_h() {
+        let rough = Converter::new(Type::TLG).with_rough_h(true);
+        let default = Converter::new(Type::Default).with_rough_h(true);
+        // Standard betacode always reads h as eta.
+        assert_eq!(to_greek("ho", Type::TLG).unwrap(), "ηο");
+        assert_eq!(to_greek("ho", Type::Default).unwrap(), "ηο");
+        assert_eq!(rough.to_greek("ho").unwrap(), "ὁ");
+        assert_eq!(default.to_greek("ho").unwrap(), "ὁ");
+        assert_eq!(rough.to_greek("ho lo/gos").unwrap(), "ὁ λόγος");
+        assert_eq!(rough.to_greek("hh").unwrap(), "ἡ");
+        assert_eq!(rough.to_greek("ho/s, hw=|").unwrap(), "ὅς, ᾧ");
+        assert_eq!(
+            rough.to_greek("hoi( ").unwrap_err(),
+            ConversionError::UnexpectedAccent('ι', 2)
+        );
+        // Diphthongs take the breathing on their second vowel.
+        assert_eq!(rough.to_greek("hoi").unwrap(), "οἱ");
+        assert_eq!(
+            rough.to_greek("hui(o/s").unwrap_err(),
+            ConversionError::UnexpectedAccent('ι', 2)
+        );
+        assert_eq!(rough.to_greek("huio/s").unwrap(), "υἱός");
+        assert_eq!(rough.to_greek("hou=tos").unwrap(), "οὗτος");
+        assert_eq!(rough.to_greek("hai+").unwrap(), "ἁϊ");
+        // Only an h at the start of a word, before a vowel, is a breathing.
+        assert_eq!(rough.to_greek("h").unwrap(), "η");
+        assert_eq!(rough.to_greek("hn").unwrap(), "ην");
+        assert_eq!(rough.to_greek("h(").unwrap(), "ἡ");
+        assert_eq!(rough.to_greek("h)=n").unwrap(), "ἦν");
+        assert_eq!(rough.to_greek("lhei").unwrap(), "ληει");
+        assert_eq!(rough.to_greek("kai\\ ho").unwrap(), "καὶ ὁ");
+        assert_eq!(
+            rough.to_greek("(ho)").unwrap_err(),
+            ConversionError::UnexpectedCharacter('(', 0)
+        );
+        assert_eq!(
+            rough
+                .with_punctuation(PunctuationPolicy::Passthrough)
+                .to_greek("[ho]")
+                .unwrap(),
+            "[ὁ]"
+        );
+        // A capital H makes the vowel a capital.
+        assert_eq!(default.to_greek("Ho").unwrap(), "Ὁ");
+        assert_eq!(default.to_greek("Hoi").unwrap(), "Οἱ");
+        assert_eq!(default.to_greek("HO").unwrap(), "Ὁ");
+        assert_eq!(rough.to_greek("*ho").unwrap(), "Ηο");
+        assert_eq!(rough.to_greek("*(o").unwrap(), "Ὁ");
+    }
+
+    #[test]
+    fn letters() {
+        assert_eq!(greek_letter(b'q', Type::TLG), Some('θ'));
+        assert_eq!(greek_letter(b'Q', Type::TLG), Some('θ'));
+        assert_eq!(greek_letter(b'x', Type::TLG), Some('χ'));
+        assert_eq!(greek_letter(b'c', Type::TLG), Some('ξ'));
+        assert_eq!(greek_letter(b'v', Type::TLG), Some('ϝ'));
+        assert_eq!(greek_letter(b'Q', Type::Default), Some('Θ'));
+        assert_eq!(greek_letter(b'c', Type::Default), Some('χ'));
+        assert_eq!(greek_letter(b'x', Type::Default), None);
+        assert_eq!(greek_letter(b'*', Type::TLG), None);
+        assert_eq!(greek_letter(b')', Type::TLG), None);
+        assert_eq!(greek_letter(0, Type::TLG), None);
+        assert_eq!(greek_letter(0xce, Type::TLG), None);
+        for version in [Type::Default, Type::TLG, Type::Perseus] {
+            assert!(is_betacode_letter(b'q', version));
+            assert!(!is_betacode_letter(b'/', version));
+            assert!(!is_betacode_letter(b'#', version));
         }
-        if is_ascii_whitespace(c) {
-            break;
+        assert!(is_betacode_letter(b'Q', Type::TLG));
+        assert!(!is_betacode_letter(b'x', Type::Default));
+        for c in b"/\\()|+=^123" {
+            assert!(is_betacode_symbol(*c), "{}", *c as char);
         }
-        let valid = is_valid_betacode_symbol(c);
-        if valid > 0 {
-            if current == 0 as char {
-                // We see a betacode accent character, but
-                // not a greek letter just before it.
-                return Err(ConversionError::UnexpectedCharacter(
-                    c as char,
-                    current_index,
-                ));
+        assert!(!is_betacode_symbol(b'#'));
+        assert!(!is_betacode_symbol(b'q'));
+        assert!(!is_betacode_symbol(b'*'));
+        assert!(!is_betacode_symbol(b'%'));
+        // Every letter is converted in the same way as a single letter word.
+        for version in [Type::Default, Type::TLG, Type::Perseus] {
+            for c in b'a'..=b'z' {
+                let word = to_greek_bytes(&[c], version).ok();
+                let letter = greek_letter(c, version).map(|l| match l {
+                    'σ' => 'ς',
+                    l => l,
+                });
+                assert_eq!(word, letter.map(String::from), "{}", c as char);
             }
-            accents = accents | valid;
-            i += 1;
-            continue;
         }
-        // This character is not an alphabetic letter, not a
-        // whitespace, and not a valid betacode symbol.
-        break;
     }
 
-    // When the end of string is reached, a final character
-    // may be waiting to be pushed onto the result string.
-    if current != 0 as char {
-        let e = apply_accent(current, accents);
-        if accents == 0 && current == 'σ' {
-            word.push('ς')
-        } else if e > 0 as char {
-            word.push(e)
-        } else {
-            return Err(ConversionError::UnexpectedAccent(
-                current as char,
-                current_index,
-            ));
+    #[test]
+    fn convert_all() {
+        let converter = Converter::new(Type::TLG);
+        assert_eq!(
+            converter.convert_all(&["kai\\ qeo/s", "", "lo/gos!", "a)/nqrwpos"]),
+            vec![
+                Ok("καὶ θεός".to_string()),
+                Ok("".to_string()),
+                Err(ConversionError::UnexpectedCharacter('!', 6)),
+                Ok("ἄνθρωπος".to_string()),
+            ]
+        );
+        assert!(converter.convert_all(&[]).is_empty());
+        let final_acute = converter.with_final_acute(true);
+        assert_eq!(
+            final_acute.convert_all(&["kalo\\n."]),
+            vec![Ok("καλόν.".to_string())]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_convert_all() {
+        let inputs: Vec<String> = (0..1000)
+            .map(|n| match n % 4 {
+                0 => "kai\\ qeo/s".to_string(),
+                1 => "e)n a)rxh=| h)=n o( lo/gos".repeat(n % 7),
+                2 => format!("lo/gos{}", n),
+                _ => "*)abraa/m".to_string(),
+            })
+            .collect();
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        for version in [Type::Default, Type::TLG] {
+            let converter = Converter::new(version);
+            assert_eq!(
+                super::par_convert_all(&inputs, version),
+                converter.convert_all(&inputs)
+            );
         }
+        assert!(super::par_convert_all(&[], Type::TLG).is_empty());
     }
 
-    if i < size && text[i] == b'\'' {
-        word.push('᾽');
-        i += 1
+    #[test]
+    fn lines() {
+        let lines = convert_lines("kai\\ qeo\\s\n h)=n o( lo/gos!\r\nkai/\n", Type::TLG);
+        assert_eq!(
+            lines,
+            vec![
+                Ok("καὶ θεὸς".to_string()),
+                Err((1, ConversionError::UnexpectedCharacter('!', 15))),
+                Ok("καί".to_string()),
+                Ok("".to_string()),
+            ]
+        );
+        assert_eq!(convert_lines("", Type::TLG), vec![Ok("".to_string())]);
     }
 
-    loop {
-        if i == size {
-            break;
-        }
-        if is_ascii_whitespace(text[i]) {
-            i += 1;
-            continue;
+    #[test]
+    fn contradictory_accents() {
+        for (betacode, letter) in [
+            ("a/\\", 'α'),
+            ("a\\/", 'α'),
+            ("a()", 'α'),
+            ("a)(/", 'α'),
+            ("a=/", 'α'),
+            ("a//", 'α'),
+            ("a))", 'α'),
+            ("a||", 'α'),
+            ("a)/|\\", 'α'),
+            ("i++", 'ι'),
+            ("s12", 'σ'),
+            ("a%26%27", 'α'),
+            ("a%27%27", 'α'),
+        ] {
+            assert_eq!(
+                to_greek(betacode, Type::TLG).unwrap_err(),
+                ConversionError::UnexpectedAccent(letter, 0),
+                "{}",
+                betacode
+            );
         }
-        // Unexpected character
-        return Err(ConversionError::UnexpectedCharacter(current as char, i));
+        assert_eq!(
+            to_greek("*)a)", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('Α', 2)
+        );
+        assert_eq!(
+            to_greek("*()a", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('Α', 3)
+        );
+        assert_eq!(
+            Converter::new(Type::TLG)
+                .with_adscript(true)
+                .to_greek("a/\\|")
+                .unwrap_err(),
+            ConversionError::UnexpectedAccent('α', 0)
+        );
+        assert_eq!(to_greek_lossy("a/\\ kai\\", Type::TLG), "\u{fffd} καὶ");
+        // The order of accents does not matter
+        assert_eq!(to_greek("a/)", Type::TLG).unwrap(), "ἄ");
+        assert_eq!(to_greek("a)/", Type::TLG).unwrap(), "ἄ");
+        assert_eq!(to_greek("a|=(", Type::TLG).unwrap(), "ᾇ");
     }
 
-    Ok(word)
-}
+    #[test]
+    fn coronis() {
+        let converter = Converter::new(Type::TLG).with_coronis(true);
+        assert_eq!(converter.to_greek("ka)gw/").unwrap(), "κα\u{343}γώ");
+        assert_eq!(
+            converter.to_greek("ta)/lla").unwrap(),
+            "τα\u{343}\u{301}λλα"
+        );
+        assert_eq!(
+            converter.to_greek("tou)/noma").unwrap(),
+            "του\u{343}\u{301}νομα"
+        );
+        assert_eq!(converter.to_greek("ka)n").unwrap(), "κα\u{343}ν");
+        assert_eq!(converter.to_greek("*ka)gw/").unwrap(), "Κα\u{343}γώ");
+        // A smooth breathing at the start of a word is not crasis.
+        assert_eq!(converter.to_greek("ou)k e)gw/").unwrap(), "οὐκ ἐγώ");
+        assert_eq!(
+            converter.to_greek("ka)gw/ e)gw/").unwrap(),
+            "κα\u{343}γώ ἐγώ"
+        );
+        assert_eq!(converter.to_greek("pu/r)r(os").unwrap(), "πύῤῥος");
+        // Crasis is guessed from the breathing, so a rough breathing is kept.
+        assert_eq!(converter.to_greek("a(nh/r").unwrap(), "ἁνήρ");
+        assert_eq!(to_greek("ka)gw/", Type::TLG).unwrap(), "κἀγώ");
+        assert_eq!(to_betacode("κα\u{343}γώ", Type::TLG).unwrap(), "ka)gw/");
+        assert_eq!(
+            converter.with_nfd(true).to_greek("ka)gw/").unwrap(),
+            "κα\u{313}γω\u{301}"
+        );
+    }
 
-// test if a character is a valid accentuation for a Greek character.
-//
-// See: https://stephanus.tlg.uci.edu/encoding/BCM.pdf
-#[inline]
-fn is_valid_betacode_symbol(c: u8) -> u16 {
-    match c {
-        b'/' => ASCII_ACUTE,
-        b'\\' => ASCII_GRAVE,
-        b'(' => ASCII_ROUGH,
-        b')' => ASCII_SMOOTH,
-        b'|' => ASCII_IOTA,
-        b'+' => ASCII_DIAERESIS,
-        b'=' => ASCII_CIRCUMFLEX,
-        b'^' => ASCII_CIRCUMFLEX,
-        b'1' => ASCII_SIGMA1,
-        b'2' => ASCII_SIGMA2,
-        b'3' => ASCII_SIGMA3,
-        _ => 0,
+    #[test]
+    fn drop_breathings() {
+        let converter = Converter::new(Type::TLG).with_drop_breathings(true);
+        assert_eq!(converter.to_greek("*(ellas").unwrap(), "Ελλας");
+        assert_eq!(converter.to_greek_upper("*(ellas").unwrap(), "ΕΛΛΑΣ");
+        assert_eq!(converter.to_greek("*(E*L*L*A*S").unwrap(), "ΕΛΛΑΣ");
+        assert_eq!(to_greek("*(ellas", Type::TLG).unwrap(), "Ἑλλας");
+        assert_eq!(to_greek("*(/ellas", Type::TLG).unwrap(), "Ἕλλας");
+        assert_eq!(converter.to_greek("*(/ellas").unwrap(), "Έλλας");
+        assert_eq!(converter.to_greek("o( a)nh/r").unwrap(), "ο ανήρ");
+        assert_eq!(converter.to_greek("r(h/twr").unwrap(), "ρήτωρ");
+        assert_eq!(converter.to_greek("w(=|").unwrap(), "ῷ");
+        assert_eq!(converter.to_greek("a()").unwrap(), "α");
+        assert_eq!(
+            Converter::new(Type::Default)
+                .with_drop_breathings(true)
+                .to_greek("E(llas")
+                .unwrap(),
+            "Ελλας"
+        );
     }
-}
 
-const ASCII_ACUTE: u16 = 0x1;
-const ASCII_GRAVE: u16 = 0x2;
-const ASCII_CIRCUMFLEX: u16 = 0x4;
-const ASCII_DIAERESIS: u16 = 0x8;
-const ASCII_ROUGH: u16 = 0x10;
-const ASCII_SMOOTH: u16 = 0x20;
-const ASCII_IOTA: u16 = 0x40;
-const ASCII_SIGMA1: u16 = 0x80;
-const ASCII_SIGMA2: u16 = 0x100;
-const ASCII_SIGMA3: u16 = 0x200;
+    #[test]
+    fn stats() {
+        let input = "*)en a)rxh=| h)=n o( lo/gos, kai\\ o( lo/gos h)=n pro\\s to\\n qeo/n.";
+        let expected = Stats {
+            letters: 36,
+            accented_vowels: 13,
+            words: 12,
+            final_sigmas: 3,
+        };
+        let (greek, stats) = to_greek_with_stats(input, Type::TLG).unwrap();
+        assert_eq!(greek, to_greek(input, Type::TLG).unwrap());
+        assert_eq!(stats, expected);
+        let nfd = Converter::new(Type::TLG).with_nfd(true);
+        assert_eq!(nfd.to_greek_with_stats(input).unwrap().1, expected);
+        let (_, stats) = to_greek_with_stats("a%27)/| *u)= r(", Type::TLG).unwrap();
+        assert_eq!(stats.accented_vowels, 2);
+        assert_eq!(stats.letters, 3);
+        let (greek, stats) = to_greek_with_stats("#awka#", Type::TLG).unwrap();
+        assert_eq!(greek, "\u{375}αωκα\u{374}");
+        assert_eq!(stats.letters, 4);
+        assert_eq!(stats.words, 1);
+        let (_, stats) = to_greek_with_stats("a)p' s1 s2", Type::TLG).unwrap();
+        assert_eq!(stats.words, 3);
+        assert_eq!(stats.final_sigmas, 1);
+        assert_eq!(
+            to_greek_with_stats("", Type::TLG).unwrap().1,
+            Stats::default()
+        );
+        assert_eq!(
+            to_greek_with_stats("qeo/s!", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 5)
+        );
+    }
 
-const ASCII_SMOOTH_ACUTE: u16 = ASCII_SMOOTH + ASCII_ACUTE;
-const ASCII_SMOOTH_GRAVE: u16 = ASCII_SMOOTH + ASCII_GRAVE;
-const ASCII_ROUGH_ACUTE: u16 = ASCII_ROUGH + ASCII_ACUTE;
-const ASCII_ROUGH_GRAVE: u16 = ASCII_ROUGH + ASCII_GRAVE;
-const ASCII_CIRCUMFLEX_ROUGH: u16 = ASCII_ROUGH + ASCII_CIRCUMFLEX;
-const ASCII_CIRCUMFLEX_SMOOTH: u16 = ASCII_SMOOTH + ASCII_CIRCUMFLEX;
-const ASCII_DIAERESIS_ACUTE: u16 = ASCII_DIAERESIS + ASCII_ACUTE;
-const ASCII_DIAERESIS_GRAVE: u16 = ASCII_DIAERESIS + ASCII_GRAVE;
+    #[test]
+    fn caret_circumflex() {
+        for version in [Type::Default, Type::TLG] {
+            assert_eq!(to_greek("a=", version).unwrap(), "ᾶ");
+            assert_eq!(to_greek("a^", version).unwrap(), "ᾶ");
+            assert_eq!(to_greek("w(^|", version).unwrap(), "ᾧ");
+            assert_eq!(to_betacode("ᾶ", version).unwrap(), "a=");
+            assert_eq!(
+                to_betacode(&to_greek("tou^", version).unwrap(), version).unwrap(),
+                "tou="
+            );
+            assert_eq!(
+                to_greek("a=^", version).unwrap_err(),
+                ConversionError::UnexpectedAccent('α', 0)
+            );
+        }
+    }
 
-#[inline]
-fn is_ascii_whitespace(c: u8) -> bool {
-    if c == b' ' || c == b'\r' || c == b'\n' || c == b'\t' || c == 0 {
-        return true;
+    #[test]
+    fn elision() {
+        assert_eq!(to_greek("d' e)gw/", Type::TLG).unwrap(), "δ᾽ ἐγώ");
+        assert_eq!(to_greek("a)ll' h)", Type::TLG).unwrap(), "ἀλλ᾽ ἠ");
+        assert_eq!(
+            to_greek("a)p' au)tou= d' e)gw/", Type::TLG).unwrap(),
+            "ἀπ᾽ αὐτοῦ δ᾽ ἐγώ"
+        );
+        assert_eq!(to_greek("a)ll',", Type::TLG).unwrap(), "ἀλλ᾽,");
+        assert_eq!(
+            to_greek("'", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\'', 0)
+        );
+        assert_eq!(
+            to_greek("kai\\ ' e)gw/", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\'', 5)
+        );
+        assert!(to_greek("a)ll'a", Type::TLG).is_err());
+        assert!(to_greek("a)ll''", Type::TLG).is_err());
     }
-    return false;
-}
 
-fn lookup_greek_letter(c: u8, version: Type) -> char {
-    let o = match c {
-        b'a' => 'α',
-        b'b' => 'β',
-        b'd' => 'δ',
-        b'e' => 'ε',
-        b'f' => 'φ',
-        b'g' => 'γ',
-        b'h' => 'η',
-        b'i' => 'ι',
-        b'k' => 'κ',
-        b'l' => 'λ',
-        b'm' => 'μ',
-        b'n' => 'ν',
-        b'o' => 'ο',
-        b'p' => 'π',
-        b'q' => 'θ',
-        b'r' => 'ρ',
-        b's' => 'σ',
-        b't' => 'γ',
-        b'u' => 'υ',
-        b'w' => 'ω',
-        b'y' => 'ψ',
-        b'z' => 'ζ',
-        b'A' => 'α',
-        b'B' => 'Β',
-        b'D' => 'Δ',
-        b'E' => 'Ε',
-        b'F' => 'Φ',
-        b'G' => 'Γ',
-        b'H' => 'Η',
-        b'I' => 'Ι',
-        b'K' => 'Κ',
-        b'L' => 'Λ',
-        b'M' => 'Μ',
-        b'N' => 'Ν',
-        b'O' => 'Ο',
-        b'Q' => 'Θ',
-        b'R' => 'Ρ',
-        b'S' => 'Σ',
-        b'T' => 'Γ',
-        b'U' => 'Υ',
-        b'W' => 'Ω',
-        b'Y' => 'Ψ',
-        b'Z' => 'Ζ',
-        _ => 0 as char,
-    };
-    if o != 0 as char {
-        return o;
+    #[test]
+    fn token_errors() {
+        let converter = Converter::new(Type::TLG).with_token_errors(true);
+        let token = |token: &str, start, end, reason| ConversionError::InvalidToken {
+            token: token.to_string(),
+            start,
+            end,
+            reason: Box::new(reason),
+        };
+        assert_eq!(
+            converter.to_greek("kai\\ qeo!s lo/gos").unwrap_err(),
+            token("qeo!s", 5, 10, ConversionError::UnexpectedCharacter('!', 8))
+        );
+        assert_eq!(
+            converter.to_greek("a/\\").unwrap_err(),
+            token("a/\\", 0, 3, ConversionError::UnexpectedAccent('α', 0))
+        );
+        assert_eq!(
+            converter.to_greek("a ) b").unwrap_err(),
+            token(")", 2, 3, ConversionError::UnexpectedCharacter(')', 2))
+        );
+        let e = converter.to_greek(" \tqeo!s\n").unwrap_err();
+        assert_eq!(e.byte_index(), 5);
+        assert_eq!(
+            e.to_string(),
+            "invalid token 'qeo!s' at bytes 2..7: unexpected character '!' at byte 5"
+        );
+        assert_eq!(
+            converter.to_greek_all_errors("qeo!s kai\\ lo/g)os").1,
+            [
+                token("qeo!s", 0, 5, ConversionError::UnexpectedCharacter('!', 3)),
+                token(
+                    "lo/g)os",
+                    11,
+                    18,
+                    ConversionError::UnexpectedAccent('γ', 14)
+                ),
+            ]
+        );
+        // Text converted a few words at a time gives the same span.
+        let chars: Vec<_> = GreekChars::new("lo/gos kai\\ qeo!s".bytes(), converter).collect();
+        assert_eq!(
+            chars.last(),
+            Some(&Err(token(
+                "qeo!s",
+                12,
+                17,
+                ConversionError::UnexpectedCharacter('!', 15)
+            )))
+        );
+        // By default only the invalid character is returned.
+        assert_eq!(
+            to_greek("kai\\ qeo!s", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 8)
+        );
     }
 
-    match version {
-        // Who uses these mpapings
-        Type::Default => {
-            let o = match c {
-                b'v' => 'σ',
-                b'V' => 'Σ',
-                b'j' => 'ς', // Some betacode systems use j for final sigma
-                b'J' => 'Σ', // Some betacode systems use j for final sigma
-                b'c' => 'χ',
-                b'C' => 'χ',
-                _ => 0 as char,
-            };
-            if o != 0 as char {
-                return o;
+    // Check that converting a word at a time, with GreekChars, words and
+    // a GreekWriter written one byte at a time, gives the Greek converted
+    // all at once.
+    fn assert_word_at_a_time(converter: Converter, text: &str) {
+        let expected = converter.to_greek(text).unwrap();
+        let chars = GreekChars::new(text.bytes(), converter);
+        assert_eq!(chars.collect::<Result<String, _>>().unwrap(), expected);
+        let words: Result<Vec<String>, _> = converter.words(text).collect();
+        assert_eq!(words.unwrap().join(" "), expected);
+        #[cfg(feature = "std")]
+        {
+            use std::io::Write;
+            let mut writer = GreekWriter::new(Vec::new(), converter);
+            for b in text.bytes() {
+                writer.write_all(&[b]).unwrap();
             }
+            let greek = writer.into_inner().unwrap();
+            assert_eq!(std::str::from_utf8(&greek).unwrap(), expected);
         }
-        Type::TLG => {
-            let o = match c {
-                b'v' => 'ϝ',
-                b'V' => 'Ϝ',
-                b'c' => 'ξ',
-                b'C' => 'Ξ',
-                b'x' => 'χ',
-                b'X' => 'Χ',
-                _ => 0 as char,
-            };
-            if o != 0 as char {
-                return o;
-            }
+    }
+
+    #[test]
+    fn spaced_elision() {
+        let converter = Converter::new(Type::TLG).with_spaced_elision(true);
+        assert_eq!(converter.to_greek("a)ll ' e)gw/").unwrap(), "ἀλλ᾽ ἐγώ");
+        assert_eq!(converter.to_greek("a)ll  '\ne)gw/").unwrap(), "ἀλλ᾽ ἐγώ");
+        assert_eq!(converter.to_greek("a)ll 'e)gw/").unwrap(), "ἀλλ᾽ ἐγώ");
+        assert_eq!(converter.to_greek("d ' ").unwrap(), "δ᾽");
+        assert_eq!(converter.to_greek("a)ll ',").unwrap(), "ἀλλ᾽,");
+        // The unspaced form is read as before.
+        assert_eq!(converter.to_greek("a)ll' e)gw/").unwrap(), "ἀλλ᾽ ἐγώ");
+        // Only an apostrophe after a word is an elision mark.
+        assert_eq!(
+            converter.to_greek("' e)gw/").unwrap_err(),
+            ConversionError::UnexpectedCharacter('\'', 0)
+        );
+        assert_eq!(
+            converter.to_greek("a, ' b").unwrap_err(),
+            ConversionError::UnexpectedCharacter('\'', 3)
+        );
+        assert_eq!(
+            converter.to_greek("a)ll ' ' b").unwrap_err(),
+            ConversionError::UnexpectedCharacter('\'', 7)
+        );
+        assert_eq!(
+            to_greek("a)ll ' e)gw/", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\'', 5)
+        );
+
+        // Converting a word at a time gives the same Greek.
+        for text in ["a)ll ' e)gw/", "a)ll  '\ne)gw/", "d ' a)ll ' ", "a)ll ',"] {
+            assert_word_at_a_time(converter, text);
         }
+        let errors: Vec<_> = Converter::new(Type::TLG).words("a)ll ' e)gw/").collect();
+        assert_eq!(
+            errors,
+            [
+                Err(ConversionError::UnexpectedCharacter('\'', 5)),
+                Ok("ἐγώ".to_string())
+            ]
+        );
     }
 
-    0 as char
-}
+    #[test]
+    fn betacode() {
+        assert_eq!(to_betacode("", Type::TLG).unwrap(), "");
+        assert_eq!(to_betacode("θεός", Type::TLG).unwrap(), "qeo/s");
+        assert_eq!(to_betacode("Θεός", Type::TLG).unwrap(), "*qeo/s");
+        assert_eq!(to_betacode("Θεός", Type::Default).unwrap(), "Qeo/s");
+        assert_eq!(to_betacode("Ἕλλας", Type::TLG).unwrap(), "*(/ellas");
+        assert_eq!(to_betacode("Ἕλλας", Type::Default).unwrap(), "E(/llas");
+        assert_eq!(to_betacode("ᾯ", Type::TLG).unwrap(), "*(=w|");
+        assert_eq!(
+            to_betacode("καὶ ἔλεγεν αὐτοῖς\u{387}", Type::TLG).unwrap(),
+            "kai\\ e)/legen au)toi=s:"
+        );
+        assert_eq!(to_betacode("τί\u{37e}", Type::TLG).unwrap(), "ti/;");
+        assert_eq!(to_betacode("ἀπ᾽", Type::TLG).unwrap(), "a)p'");
+        assert_eq!(to_betacode("ᾶ", Type::TLG).unwrap(), "a=");
+        assert_eq!(to_betacode("α\u{342}", Type::Default).unwrap(), "a=");
+        assert_eq!(to_betacode("ἀπ'", Type::TLG).unwrap(), "a)p'");
+        assert_eq!(to_betacode("ἀπ\u{2019}", Type::TLG).unwrap(), "a)p'");
+        assert_eq!(
+            to_betacode("ἀλλ\u{2019} ἐγώ", Type::Default).unwrap(),
+            to_betacode("ἀλλ᾽ ἐγώ", Type::Default).unwrap()
+        );
+        assert_eq!(
+            to_greek(&to_betacode("ἀπ\u{2019}", Type::TLG).unwrap(), Type::TLG).unwrap(),
+            "ἀπ᾽"
+        );
+        assert_eq!(to_betacode("σ ςα ϲ Ϲ", Type::TLG).unwrap(), "s1 s2a s3 *s3");
+        assert_eq!(to_betacode("χξ", Type::TLG).unwrap(), "xc");
+        assert_eq!(to_betacode("χ", Type::Default).unwrap(), "c");
+        assert_eq!(to_betacode("ϟϛ", Type::TLG).unwrap(), "#1#2");
+        assert_eq!(
+            to_betacode("θεός!", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('!', 8)
+        );
+        assert_eq!(
+            to_betacode("β\u{301}", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('β', 0)
+        );
+        assert_eq!(
+            to_betacode("\u{301}", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('\u{301}', 0)
+        );
+    }
 
-fn apply_accent(c: char, accents: u16) -> char {
-    if accents == 0 {
-        return c;
+    #[test]
+    fn betacode_combining() {
+        use unicode_normalization::UnicodeNormalization;
+        let words = ["ἔ", "Ἐν ἀρχῇ ἦν ὁ λόγος", "ᾯ ῥήματα Ϋ́ ΐ ὖ ᾱ́"];
+        for version in [Type::Default, Type::TLG] {
+            for word in words {
+                let nfc = to_betacode(&word.nfc().collect::<String>(), version).unwrap();
+                let nfd = to_betacode(&word.nfd().collect::<String>(), version).unwrap();
+                assert_eq!(nfc, nfd);
+            }
+        }
+        assert_eq!(to_betacode("ἔ", Type::TLG).unwrap(), "e)/");
+        assert_eq!(to_betacode("ε\u{313}\u{301}", Type::TLG).unwrap(), "e)/");
+        assert_eq!(to_betacode("ε\u{301}\u{313}", Type::TLG).unwrap(), "e)/");
+        assert_eq!(to_betacode("ε\u{343}\u{341}", Type::TLG).unwrap(), "e)/");
+        assert_eq!(to_betacode("ἐ\u{301}", Type::TLG).unwrap(), "e)/");
+        assert_eq!(to_betacode("ι\u{344}", Type::TLG).unwrap(), "i+/");
+        assert_eq!(to_betacode("Υ\u{313}", Type::TLG).unwrap(), "*)u");
+        assert_eq!(
+            to_betacode("ε\u{342}", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('ε', 0)
+        );
     }
 
-    match (c, accents) {
-        ('α', ASCII_SMOOTH) => 'ἀ',
-        ('ε', ASCII_SMOOTH) => 'ἐ',
-        ('ι', ASCII_SMOOTH) => 'ἰ',
-        ('η', ASCII_SMOOTH) => 'ἠ',
-        ('o', ASCII_SMOOTH) => 'ὀ',
-        ('ω', ASCII_SMOOTH) => 'ὠ',
-        ('υ', ASCII_SMOOTH) => 'ὐ',
-        ('Α', ASCII_SMOOTH) => 'Ἀ',
-        ('Ε', ASCII_SMOOTH) => 'Ἐ',
-        ('Ι', ASCII_SMOOTH) => 'Ἰ',
-        ('Η', ASCII_SMOOTH) => 'Ἠ',
-        ('O', ASCII_SMOOTH) => 'Ὀ',
-        ('Ω', ASCII_SMOOTH) => 'Ὠ',
-        ('Υ', ASCII_SMOOTH) => 'ὐ',
-        ('α', ASCII_ROUGH) => 'ἁ',
-        ('ε', ASCII_ROUGH) => 'ἑ',
-        ('ι', ASCII_ROUGH) => 'ἱ',
-        ('η', ASCII_ROUGH) => 'ἡ',
-        ('o', ASCII_ROUGH) => 'ὁ',
-        ('ω', ASCII_ROUGH) => 'ὡ',
-        ('υ', ASCII_ROUGH) => 'ὑ',
-        ('ρ', ASCII_ROUGH) => 'ῥ',
-        ('Α', ASCII_ROUGH) => 'Ἁ',
-        ('Ε', ASCII_ROUGH) => 'Ἑ',
-        ('Ι', ASCII_ROUGH) => 'Ἱ',
-        ('Η', ASCII_ROUGH) => 'Ἡ',
-        ('O', ASCII_ROUGH) => 'Ὁ',
-        ('Ω', ASCII_ROUGH) => 'Ὡ',
-        ('Υ', ASCII_ROUGH) => 'Ὑ',
-        ('Ρ', ASCII_ROUGH) => 'Ῥ',
-        ('α', ASCII_ACUTE) => 'ά',
-        ('ε', ASCII_ACUTE) => 'έ',
-        ('ι', ASCII_ACUTE) => 'ί',
-        ('η', ASCII_ACUTE) => 'ή',
-        ('ο', ASCII_ACUTE) => 'ό',
-        ('ω', ASCII_ACUTE) => 'ώ',
-        ('υ', ASCII_ACUTE) => 'ύ',
-        ('Α', ASCII_ACUTE) => 'Ά',
-        ('Ε', ASCII_ACUTE) => 'Έ',
-        ('Ι', ASCII_ACUTE) => 'Ί',
-        ('Η', ASCII_ACUTE) => 'Ή',
-        ('O', ASCII_ACUTE) => 'Ό',
-        ('Ω', ASCII_ACUTE) => 'Ώ',
-        ('Υ', ASCII_ACUTE) => 'Ύ',
-        ('α', ASCII_GRAVE) => 'ὰ',
-        ('ε', ASCII_GRAVE) => 'ὲ',
-        ('ι', ASCII_GRAVE) => 'ὶ',
-        ('η', ASCII_GRAVE) => 'ὴ',
-        ('o', ASCII_GRAVE) => 'ὸ',
-        ('ω', ASCII_GRAVE) => 'ὼ',
-        ('υ', ASCII_GRAVE) => 'ὺ',
-        ('Α', ASCII_GRAVE) => 'Ὰ',
-        ('Ε', ASCII_GRAVE) => 'Ὲ',
-        ('Ι', ASCII_GRAVE) => 'Ὶ',
-        ('Η', ASCII_GRAVE) => 'Ὴ',
-        ('O', ASCII_GRAVE) => 'Ὸ',
-        ('Ω', ASCII_GRAVE) => 'Ὼ',
-        ('Υ', ASCII_GRAVE) => 'Ὺ',
-        ('α', ASCII_CIRCUMFLEX) => 'ᾶ',
-        ('ι', ASCII_CIRCUMFLEX) => 'ῖ',
-        ('η', ASCII_CIRCUMFLEX) => 'ῆ',
-        ('ω', ASCII_CIRCUMFLEX) => 'ῶ',
-        ('υ', ASCII_CIRCUMFLEX) => 'ῦ',
-        ('α', ASCII_IOTA) => 'ᾳ',
-        ('η', ASCII_IOTA) => 'ῃ',
-        ('ω', ASCII_IOTA) => 'ῳ',
-        ('α', ASCII_SMOOTH_GRAVE) => 'ἂ',
-        ('ε', ASCII_SMOOTH_GRAVE) => 'ἔ',
-        ('ι', ASCII_SMOOTH_GRAVE) => 'ἲ',
-        ('η', ASCII_SMOOTH_GRAVE) => 'ἢ',
-        ('o', ASCII_SMOOTH_GRAVE) => 'ὂ',
-        ('ω', ASCII_SMOOTH_GRAVE) => 'ὢ',
-        ('υ', ASCII_SMOOTH_GRAVE) => 'ὒ',
-        ('Α', ASCII_SMOOTH_GRAVE) => 'Ἂ',
-        ('Ε', ASCII_SMOOTH_GRAVE) => 'Ἒ',
-        ('Ι', ASCII_SMOOTH_GRAVE) => 'Ἲ',
-        ('Η', ASCII_SMOOTH_GRAVE) => 'Ἢ',
-        ('O', ASCII_SMOOTH_GRAVE) => 'Ὂ',
-        ('Ω', ASCII_SMOOTH_GRAVE) => 'Ὤ',
-        //('Υ', ASCII_SMOOTH_GRAVE) => '῍Υ', // Not possible to type on OS/X
-        ('α', ASCII_ROUGH_GRAVE) => 'ἃ',
-        ('ε', ASCII_ROUGH_GRAVE) => 'ἓ',
-        ('ι', ASCII_ROUGH_GRAVE) => 'ἳ',
-        ('η', ASCII_ROUGH_GRAVE) => 'ἣ',
-        ('o', ASCII_ROUGH_GRAVE) => 'ὃ',
-        ('ω', ASCII_ROUGH_GRAVE) => 'ὣ',
-        ('υ', ASCII_ROUGH_GRAVE) => 'ὓ',
-        ('Α', ASCII_ROUGH_GRAVE) => 'Ἃ',
-        ('Ε', ASCII_ROUGH_GRAVE) => 'Ἒ',
-        ('Ι', ASCII_ROUGH_GRAVE) => 'Ἳ',
-        ('Η', ASCII_ROUGH_GRAVE) => 'Ἣ',
-        ('O', ASCII_ROUGH_GRAVE) => 'Ὃ',
-        ('Ω', ASCII_ROUGH_GRAVE) => 'Ὣ',
-        ('Υ', ASCII_ROUGH_GRAVE) => 'Ὓ',
-        ('α', ASCII_SMOOTH_ACUTE) => 'ἄ',
-        ('ε', ASCII_SMOOTH_ACUTE) => 'ἔ',
-        ('ι', ASCII_SMOOTH_ACUTE) => 'ἴ',
-        ('η', ASCII_SMOOTH_ACUTE) => 'ἤ',
-        ('o', ASCII_SMOOTH_ACUTE) => 'ὄ',
-        ('ω', ASCII_SMOOTH_ACUTE) => 'ὤ',
-        ('υ', ASCII_SMOOTH_ACUTE) => 'ὔ',
-        ('Α', ASCII_SMOOTH_ACUTE) => 'Ἄ',
-        ('Ε', ASCII_SMOOTH_ACUTE) => 'Ἔ',
-        ('Ι', ASCII_SMOOTH_ACUTE) => 'Ἴ',
-        ('Η', ASCII_SMOOTH_ACUTE) => 'Ἤ',
-        ('O', ASCII_SMOOTH_ACUTE) => 'Ὄ',
-        ('Ω', ASCII_SMOOTH_ACUTE) => 'Ὤ',
-        //('Υ', ASCII_SMOOTH_ACUTE) => '῎Υ', // Seems not possible to compose
-        ('α', ASCII_ROUGH_ACUTE) => 'ἅ',
-        ('ε', ASCII_ROUGH_ACUTE) => 'ἕ',
-        ('ι', ASCII_ROUGH_ACUTE) => 'ἵ',
-        ('η', ASCII_ROUGH_ACUTE) => 'ἥ',
-        ('o', ASCII_ROUGH_ACUTE) => 'ὅ',
-        ('ω', ASCII_ROUGH_ACUTE) => 'ὥ',
-        ('υ', ASCII_ROUGH_ACUTE) => 'ὕ',
-        ('Α', ASCII_ROUGH_ACUTE) => 'Ἅ',
-        ('Ε', ASCII_ROUGH_ACUTE) => 'Ἕ',
-        ('Ι', ASCII_ROUGH_ACUTE) => 'Ἵ',
-        ('Η', ASCII_ROUGH_ACUTE) => 'Ἥ',
-        ('O', ASCII_ROUGH_ACUTE) => 'Ὅ',
-        ('Ω', ASCII_ROUGH_ACUTE) => 'Ὥ',
-        ('Υ', ASCII_ROUGH_ACUTE) => 'Ὕ',
-        ('ι', ASCII_DIAERESIS) => 'ϊ',
-        ('υ', ASCII_DIAERESIS) => 'ϋ',
-        ('Ι', ASCII_DIAERESIS) => 'Ϊ',
-        ('Υ', ASCII_DIAERESIS) => 'Ϋ',
-        ('ι', ASCII_DIAERESIS_GRAVE) => 'ῒ',
-        ('υ', ASCII_DIAERESIS_GRAVE) => 'ῢ',
-        ('Ι', ASCII_DIAERESIS_GRAVE) => 'ῒ',
-        ('Υ', ASCII_DIAERESIS_GRAVE) => 'ῢ',
-        ('ι', ASCII_DIAERESIS_ACUTE) => 'ΐ',
-        ('υ', ASCII_DIAERESIS_ACUTE) => 'ΰ',
-        ('Ι', ASCII_DIAERESIS_ACUTE) => 'ΐ',
-        ('Υ', ASCII_DIAERESIS_ACUTE) => 'ΰ',
-        ('α', ASCII_CIRCUMFLEX_SMOOTH) => 'ἆ',
-        ('η', ASCII_CIRCUMFLEX_SMOOTH) => 'ἦ',
-        ('ι', ASCII_CIRCUMFLEX_SMOOTH) => 'ἶ',
-        ('ω', ASCII_CIRCUMFLEX_SMOOTH) => 'ὦ',
-        ('υ', ASCII_CIRCUMFLEX_SMOOTH) => 'ὖ',
-        ('Α', ASCII_CIRCUMFLEX_SMOOTH) => 'Ἆ',
-        ('Η', ASCII_CIRCUMFLEX_SMOOTH) => 'Ἦ',
-        ('Ι', ASCII_CIRCUMFLEX_SMOOTH) => 'Ἶ',
-        ('Ω', ASCII_CIRCUMFLEX_SMOOTH) => 'Ὦ',
-        ('Υ', ASCII_CIRCUMFLEX_SMOOTH) => 'ὖ',
-        ('α', ASCII_CIRCUMFLEX_ROUGH) => 'ἇ',
-        ('η', ASCII_CIRCUMFLEX_ROUGH) => 'ἧ',
-        ('ι', ASCII_CIRCUMFLEX_ROUGH) => 'ἷ',
-        ('ω', ASCII_CIRCUMFLEX_ROUGH) => 'ὧ',
-        ('υ', ASCII_CIRCUMFLEX_ROUGH) => 'ὗ',
-        ('Α', ASCII_CIRCUMFLEX_ROUGH) => 'Ἇ',
-        ('Η', ASCII_CIRCUMFLEX_ROUGH) => 'Ἧ',
-        ('Ι', ASCII_CIRCUMFLEX_ROUGH) => 'Ἷ',
-        ('Ω', ASCII_CIRCUMFLEX_ROUGH) => 'Ὧ',
-        ('Υ', ASCII_CIRCUMFLEX_ROUGH) => 'Ὗ',
-        ('σ', ASCII_SIGMA1) => 'σ',
-        ('σ', ASCII_SIGMA2) => 'ς',
-        ('σ', ASCII_SIGMA3) => 'ϲ',
-        ('Σ', ASCII_SIGMA1) => 'Σ',
-        ('Σ', ASCII_SIGMA2) => 'Σ',
-        ('Σ', ASCII_SIGMA3) => 'Ϲ',
-        (_, _) => 0 as char,
+    #[test]
+    fn betacode_accents() {
+        // Grave and acute accents are told apart, whether the letter is
+        // precomposed or followed by a combining accent.
+        assert_eq!(to_betacode("ὰ", Type::TLG).unwrap(), "a\\");
+        assert_eq!(to_betacode("ά", Type::TLG).unwrap(), "a/");
+        assert_eq!(to_betacode("α\u{300}", Type::TLG).unwrap(), "a\\");
+        assert_eq!(to_betacode("α\u{301}", Type::TLG).unwrap(), "a/");
+        assert_eq!(to_betacode("ᾶ", Type::TLG).unwrap(), "a=");
+        assert_eq!(to_betacode("α\u{342}", Type::TLG).unwrap(), "a=");
+        assert_eq!(to_betacode("καὶ", Type::TLG).unwrap(), "kai\\");
+        assert_eq!(to_betacode("καί", Type::TLG).unwrap(), "kai/");
+        assert_eq!(to_betacode("Ὰ", Type::TLG).unwrap(), "*\\a");
+        assert_eq!(to_betacode("Ά", Type::Default).unwrap(), "A/");
+        assert_eq!(
+            to_betacode("ά\u{300}", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('α', 0)
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn round_trip() {
+        for version in [Type::Default, Type::TLG] {
+            let options = Converter::new(version);
+            let mut letters: Vec<char> = (0..128)
+                .map(|b| lookup_greek_letter(b, version))
+                .filter(|&l| l != 0 as char)
+                .collect();
+            letters.extend(['σ', 'ς', 'ϲ', 'Σ', 'Ϲ']);
+            for l in letters {
+                for accents in 0..0x1000 {
+                    let mut letter = Letter {
+                        c: l,
+                        accents,
+                        index: 0,
+                        final_sigma: false,
+                    };
+                    let mut greek = String::new();
+                    if push_letter(&mut greek, &options, &mut letter, false, &mut Err).is_err() {
+                        continue;
+                    }
+                    let betacode = to_betacode(&greek, version).unwrap();
+                    assert_eq!(
+                        to_greek(&betacode, version).unwrap(),
+                        greek,
+                        "{:?} {}",
+                        version,
+                        betacode
+                    );
+                    let word = greek.repeat(3);
+                    let betacode = to_betacode(&word, version).unwrap();
+                    assert_eq!(to_greek(&betacode, version).unwrap(), word);
+                }
+            }
+        }
+    }
 
     #[test]
-    fn test_traits() {
-        use crate::Betacode;
-        assert_eq!("".to_greek(Type::Default).unwrap(), "");
-        assert_eq!(" ab ".to_greek(Type::Default).unwrap(), "αβ");
-        assert_eq!(String::from("").to_greek(Type::Default).unwrap(), "");
-        assert_eq!(String::from("sa").to_greek(Type::Default).unwrap(), "σα");
+    fn capital_chi() {
+        assert_eq!(to_greek("Cristos", Type::Default).unwrap(), "Χριστος");
+        assert_eq!(to_greek("CRISTOS", Type::Default).unwrap(), "ΧΡΙΣΤΟΣ");
+        assert_eq!(to_greek("cristos", Type::Default).unwrap(), "χριστος");
+        assert_eq!(to_greek("*xristo/s", Type::TLG).unwrap(), "Χριστός");
+        assert_eq!(to_greek("*c", Type::TLG).unwrap(), "Ξ");
+        assert_eq!(to_greek("Xristos", Type::TLG).unwrap(), "χριστος");
+        assert_eq!(
+            to_greek("Xristos", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedCharacter('X', 0)
+        );
+        assert_eq!(to_betacode("Χριστός", Type::Default).unwrap(), "Cristo/s");
+        assert_eq!(to_betacode("Χριστός", Type::TLG).unwrap(), "*xristo/s");
     }
 
     #[test]
-    fn valid_default_encoding() {
-        assert_eq!(to_greek("", Type::Default).unwrap(), "");
-        assert_eq!(to_greek(" ", Type::Default).unwrap(), "");
-        assert_eq!(to_greek("  ", Type::Default).unwrap(), "");
-        assert_eq!(to_greek("a", Type::Default).unwrap(), "α");
-        assert_eq!(to_greek("a)", Type::Default).unwrap(), "ἀ");
-        assert_eq!(to_greek("s", Type::Default).unwrap(), "ς");
-        assert_eq!(to_greek("es", Type::Default).unwrap(), "ες");
-        assert_eq!(to_greek("es1", Type::Default).unwrap(), "εσ");
-        assert_eq!(to_greek("es2", Type::Default).unwrap(), "ες");
-        assert_eq!(to_greek("es3", Type::Default).unwrap(), "εϲ");
-        assert_eq!(to_greek("sos", Type::Default).unwrap(), "σος");
-        assert_eq!(to_greek("a)bba", Type::Default).unwrap(), "ἀββα");
-        assert_eq!(to_greek("a)p'", Type::Default).unwrap(), "ἀπ᾽");
-        assert_eq!(to_greek(" d' ", Type::Default).unwrap(), "δ᾽");
-        assert_eq!(to_greek(" a(ll", Type::Default).unwrap(), "ἁλλ");
-        assert_eq!(to_greek("kai\\ ", Type::Default).unwrap(), "καὶ");
-        assert_eq!(to_greek("cri", Type::Default).unwrap(), "χρι");
-        assert_eq!(to_greek("criv", Type::Default).unwrap(), "χρις");
-        assert_eq!(to_greek("Qeo/v", Type::Default).unwrap(), "Θεός");
-        assert_eq!(to_greek("qeo/s3", Type::Default).unwrap(), "θεόϲ");
+    fn perseus() {
+        assert_eq!(to_greek("qeo/j", Type::Perseus).unwrap(), "θεός");
+        assert_eq!(to_greek("*J", Type::Perseus).unwrap(), "Σ");
+        assert_eq!(to_greek("*xristo/s", Type::Perseus).unwrap(), "Χριστός");
+        assert_eq!(to_greek("do/ca", Type::Perseus).unwrap(), "δόξα");
+        assert_eq!(to_greek("*vanac", Type::Perseus).unwrap(), "Ϝαναξ");
+        assert_eq!(to_greek("#2", Type::Perseus).unwrap(), "ϛ");
+        assert_eq!(to_greek("QEO/J", Type::Perseus).unwrap(), "θεός");
+        assert_eq!(
+            to_greek("qeo/j", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('j', 4)
+        );
+        assert_eq!(greek_letter(b'j', Type::Perseus), Some('σ'));
+        assert_eq!(to_greek("ja j1 j2 j3", Type::Perseus).unwrap(), "σα σ ς ϲ");
+        let lunate = Converter::new(Type::Perseus).with_sigma(SigmaPolicy::Lunate);
+        assert_eq!(lunate.to_greek("qeoj *j").unwrap(), "θεοϲ Ϲ");
+        let medial = Converter::new(Type::Perseus).with_sigma(SigmaPolicy::Medial);
+        assert_eq!(medial.to_greek("qeo/j").unwrap(), "θεόσ");
+        assert_eq!(greek_letter(b'j', Type::TLG), None);
+        assert_eq!(to_betacode("θεός", Type::Perseus).unwrap(), "qeo/s");
+        assert_eq!(to_betacode("δόξα", Type::Perseus).unwrap(), "do/ca");
     }
 
     #[test]
@@ -603,6 +6542,199 @@ mod tests {
         assert_eq!(to_greek("qeo/s3", Type::TLG).unwrap(), "θεόϲ");
     }
 
+    #[test]
+    fn tlg_uppercase_accents() {
+        assert_eq!(to_greek("*)a", Type::TLG).unwrap(), "Ἀ");
+        assert_eq!(to_greek("*a)", Type::TLG).unwrap(), "Ἀ");
+        assert_eq!(to_greek("*(ellas", Type::TLG).unwrap(), "Ἑλλας");
+        assert_eq!(to_greek("*(/ellas", Type::TLG).unwrap(), "Ἕλλας");
+        assert_eq!(to_greek("*e(/llas", Type::TLG).unwrap(), "Ἕλλας");
+        assert_eq!(to_greek("*)/aqhnai", Type::TLG).unwrap(), "Ἄθηναι");
+        assert_eq!(to_greek("ei)s *)/aqhnas", Type::TLG).unwrap(), "εἰς Ἄθηνας");
+        assert_eq!(
+            to_greek("qeo/s *", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('*', 6)
+        );
+        assert_eq!(
+            to_greek("qeo/s\n*)", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('*', 6)
+        );
+        assert_eq!(
+            to_greek("kai\\ *)iwa/nnhs", Type::TLG).unwrap(),
+            "καὶ Ἰωάννης"
+        );
+    }
+
+    // An asterix must be followed by the capital letter it marks.
+    #[test]
+    fn lone_asterix() {
+        for (input, index) in [
+            ("*", 0),
+            ("**", 0),
+            ("*/", 0),
+            ("*)", 0),
+            ("* a", 0),
+            ("a*.", 1),
+        ] {
+            assert_eq!(
+                to_greek(input, Type::TLG).unwrap_err(),
+                ConversionError::UnexpectedCharacter('*', index),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            to_greek("*", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedCharacter('*', 0)
+        );
+        assert_eq!(to_greek_lossy("**", Type::TLG), "\u{fffd}\u{fffd}");
+        assert_eq!(to_greek_lossy("a*.", Type::TLG), "α\u{fffd}.");
+        assert_eq!(to_greek("**a", Type::TLG).unwrap_err().byte_index(), 0);
+        assert_eq!(to_greek("*%27a *#2", Type::TLG).unwrap(), "Ᾱ Ϛ");
+        assert_eq!(
+            Converter::new(Type::TLG)
+                .with_skip_markup(true)
+                .to_greek("*$a")
+                .unwrap(),
+            "Α"
+        );
+    }
+
+    #[test]
+    fn trailing_sigma_accents() {
+        assert_eq!(to_greek("o/s", Type::Default).unwrap(), "ός");
+        assert_eq!(to_greek("qeo/s", Type::TLG).unwrap(), "θεός");
+        for (input, index) in [("os/", 1), ("s/", 0), ("qeo/s/", 4), ("qeo/s) ", 4)] {
+            for version in [Type::Default, Type::TLG] {
+                assert_eq!(
+                    to_greek(input, version).unwrap_err(),
+                    ConversionError::UnexpectedAccent('σ', index),
+                    "{}",
+                    input
+                );
+            }
+        }
+        assert_eq!(
+            to_greek("*s/", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedAccent('Σ', 1)
+        );
+        assert_eq!(to_greek_lossy("qeo/s/", Type::TLG), "θεό\u{fffd}");
+        assert_eq!(
+            Converter::new(Type::TLG)
+                .with_sigma(SigmaPolicy::Medial)
+                .to_greek("os/")
+                .unwrap_err(),
+            ConversionError::UnexpectedAccent('σ', 1)
+        );
+    }
+
+    #[test]
+    fn under_dot() {
+        let dotted = Converter::new(Type::TLG).with_under_dot(true);
+        assert_eq!(dotted.to_greek("a?").unwrap(), "α\u{323}");
+        assert_eq!(dotted.to_greek("a/?").unwrap(), "ά\u{323}");
+        assert_eq!(dotted.to_greek("a?/").unwrap(), "ά\u{323}");
+        assert_eq!(dotted.to_greek("*?a").unwrap(), "Α\u{323}");
+        assert_eq!(dotted.to_greek("qeo/s?").unwrap(), "θεός\u{323}");
+        assert_eq!(dotted.to_greek("a%27/?").unwrap(), "ᾱ\u{323}\u{301}");
+        assert_eq!(
+            dotted.with_nfd(true).to_greek("w(=|?").unwrap(),
+            "ω\u{323}\u{314}\u{342}\u{345}"
+        );
+        assert_eq!(
+            dotted.to_greek("a??").unwrap_err(),
+            ConversionError::UnexpectedAccent('α', 0)
+        );
+        assert_eq!(
+            dotted.to_greek("?").unwrap_err(),
+            ConversionError::UnexpectedCharacter('?', 0)
+        );
+        assert_eq!(
+            to_greek("a?", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('?', 1)
+        );
+        assert_eq!(dotted.to_greek_lossy("? a?"), "\u{fffd} α\u{323}");
+    }
+
+    #[test]
+    fn tlg_archaic_letters() {
+        assert_eq!(to_greek("#1", Type::TLG).unwrap(), "ϟ");
+        assert_eq!(to_greek("#2", Type::TLG).unwrap(), "ϛ");
+        assert_eq!(to_greek("#3", Type::TLG).unwrap(), "ϙ");
+        assert_eq!(to_greek("#5", Type::TLG).unwrap(), "ϡ");
+        assert_eq!(to_greek("*#1 *#2 *#3 *#5", Type::TLG).unwrap(), "Ϟ Ϛ Ϙ Ϡ");
+        // 96 and 906
+        assert_eq!(to_greek("#1#2", Type::TLG).unwrap(), "ϟϛ");
+        assert_eq!(to_greek("#5#2", Type::TLG).unwrap(), "ϡϛ");
+        assert_eq!(to_greek("ri#2", Type::TLG).unwrap(), "ριϛ");
+        assert_eq!(
+            to_greek("#4", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('#', 0)
+        );
+        assert_eq!(
+            to_greek("a#12", Type::TLG).unwrap_err(),
+            ConversionError::UnexpectedCharacter('#', 1)
+        );
+        assert!(to_greek("#1", Type::Default).is_err());
+    }
+
+    #[test]
+    fn numerals() {
+        for version in [Type::Default, Type::TLG] {
+            assert_eq!(to_greek("a#", version).unwrap(), "α\u{374}");
+            assert_eq!(to_greek("#b", version).unwrap(), "\u{375}β");
+            assert_eq!(to_greek("#awka#", version).unwrap(), "\u{375}αωκα\u{374}");
+            assert_eq!(
+                to_greek("e)n e)/tei #awka#.", version).unwrap(),
+                "ἐν ἔτει \u{375}αωκα\u{374}."
+            );
+            assert_eq!(to_greek("s#", version).unwrap(), "σ\u{374}");
+            assert_eq!(
+                to_betacode("\u{375}αωκα\u{374}", version).unwrap(),
+                "#awka#"
+            );
+            assert_eq!(to_betacode("ς\u{374}", version).unwrap(), "s2#");
+        }
+        for (betacode, greek) in [
+            ("#bka#", "\u{375}βκα\u{374}"),
+            ("#a", "\u{375}α"),
+            ("#a#5#1q#", "\u{375}αϡϟθ\u{374}"),
+            ("#5#3q#", "ϡϙθ\u{374}"),
+            ("#i#", "\u{375}ι\u{374}"),
+            ("#*b*k*a#", "\u{375}ΒΚΑ\u{374}"),
+            ("#2#", "ϛ\u{374}"),
+            ("r#2#", "ρϛ\u{374}"),
+        ] {
+            assert_eq!(to_greek(betacode, Type::TLG).unwrap(), greek);
+        }
+        for (betacode, index) in [
+            ("#", 0),
+            ("# a", 0),
+            ("#/a", 0),
+            ("kai/#", 4),
+            ("kk#", 2),
+            ("a)#", 2),
+            ("ak#", 2),
+            ("aa#", 2),
+            ("#abb#", 4),
+            ("#w#i#", 4),
+            ("a##", 2),
+        ] {
+            assert_eq!(
+                to_greek(betacode, Type::TLG).unwrap_err(),
+                ConversionError::UnexpectedCharacter('#', index),
+                "{}",
+                betacode
+            );
+        }
+        assert_eq!(to_greek("#h", Type::TLG).unwrap(), "\u{375}η");
+        assert_eq!(to_greek("#2", Type::TLG).unwrap(), "ϛ");
+        assert_eq!(
+            to_greek("#2", Type::Default).unwrap_err(),
+            ConversionError::UnexpectedCharacter('#', 0)
+        );
+    }
+
     #[test]
     fn invalid_tlg_encoding() {
         assert!(to_greek("a\\b'a", Type::TLG).is_err());